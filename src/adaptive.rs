@@ -0,0 +1,103 @@
+use crate::history::Record;
+use crate::stats::KeyStats;
+use std::collections::BTreeMap;
+
+// how many letters and bigrams are practiced at once
+const TARGETS: usize = 3;
+// less data than this says nothing about a key or bigram
+const MIN_PRESSES: usize = 5;
+// an error weighs as much as being this many times slower than average
+const ERROR_WEIGHT: f64 = 5.;
+
+// the letters and bigrams the history says are the weakest
+pub struct Weaknesses {
+    pub letters: Vec<char>,
+    pub bigrams: Vec<String>,
+    // share of the generated words that contain a weakness, from 0 to 1
+    pub strength: f64,
+}
+
+impl Weaknesses {
+    pub fn from_history(records: &[Record], strength: f64) -> Weaknesses {
+        let mut letters: BTreeMap<char, KeyStats> = BTreeMap::new();
+        let mut bigrams: BTreeMap<String, KeyStats> = BTreeMap::new();
+        for record in records {
+            for (c, stats) in record.keys.iter().filter(|(c, _)| c.is_alphabetic()) {
+                letters
+                    .entry(c.to_ascii_lowercase())
+                    .or_default()
+                    .add(stats);
+            }
+            for (bigram, stats) in &record.bigrams {
+                if bigram.chars().all(char::is_alphabetic) {
+                    bigrams.entry(bigram.to_lowercase()).or_default().add(stats);
+                }
+            }
+        }
+
+        Weaknesses {
+            letters: weakest(&letters),
+            bigrams: weakest(&bigrams),
+            strength,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty() && self.bigrams.is_empty()
+    }
+
+    // whether practicing the word helps with any of the weaknesses
+    pub fn targets(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.letters.iter().any(|&letter| word.contains(letter))
+            || self
+                .bigrams
+                .iter()
+                .any(|bigram| word.contains(bigram.as_str()))
+    }
+
+    pub fn describe(&self) -> String {
+        self.letters
+            .iter()
+            .map(char::to_string)
+            .chain(self.bigrams.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// the keys with the worst mix of errors and slowness compared to the average
+fn weakest<K: Clone + Ord>(stats: &BTreeMap<K, KeyStats>) -> Vec<K> {
+    let stats: Vec<(&K, &KeyStats)> = stats
+        .iter()
+        .filter(|(_, stats)| stats.presses >= MIN_PRESSES)
+        .collect();
+
+    let latencies: Vec<f64> = stats
+        .iter()
+        .filter_map(|(_, stats)| stats.average_latency())
+        .map(|latency| latency.as_secs_f64())
+        .collect();
+    let average_latency = latencies.iter().sum::<f64>() / latencies.len().max(1) as f64;
+
+    let mut scores: Vec<(&K, f64)> = stats
+        .iter()
+        .map(|(key, stats)| {
+            let slowness = match stats.average_latency() {
+                Some(latency) if average_latency > 0. => {
+                    (latency.as_secs_f64() / average_latency - 1.).max(0.)
+                }
+                _ => 0.,
+            };
+            (*key, stats.error_rate() * ERROR_WEIGHT + slowness)
+        })
+        .filter(|(_, score)| *score > 0.)
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    scores
+        .into_iter()
+        .take(TARGETS)
+        .map(|(key, _)| key.clone())
+        .collect()
+}
@@ -0,0 +1,263 @@
+use crate::normalize::{self, Normalization};
+use crate::storage;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const BOOKMARKS_FILE: &str = "bookmarks";
+// roughly how much of the book is handed out per test
+const CHUNK_CHARS: usize = 400;
+
+pub struct Book {
+    // hash of the file content, so renaming or moving the file keeps the bookmark
+    hash: String,
+    // whitespace is collapsed to single spaces, offsets are in chars of this
+    text: Vec<char>,
+    offset: usize,
+    // where the last handed out chunk starts, retrying it must not move the bookmark twice
+    chunk_start: usize,
+}
+
+impl Book {
+    pub fn open(path: &Path, normalization: &Normalization) -> io::Result<Book> {
+        let content = fs::read_to_string(path)?;
+        let hash = storage::content_hash(content.as_bytes());
+
+        let is_markdown = path
+            .extension()
+            .is_some_and(|extension| extension == "md" || extension == "markdown");
+        let content = if is_markdown {
+            strip_markdown(&content)
+        } else {
+            content
+        };
+        let content = normalize::normalize(&content, normalization);
+        let text: Vec<char> = content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .collect();
+        if text.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} contains no text", path.display()),
+            ));
+        }
+
+        let offset = load_bookmarks()?
+            .into_iter()
+            .find(|(bookmark_hash, _)| *bookmark_hash == hash)
+            .map_or(0, |(_, offset)| offset);
+
+        Ok(Book {
+            hash,
+            text,
+            offset,
+            chunk_start: offset,
+        })
+    }
+
+    // the next piece of the book, cut at a word boundary
+    pub fn next_chunk(&mut self) -> String {
+        // a chunk typed to the end leaves the bookmark on the space before the next word,
+        // which the test would drop and never count as typed
+        while self.offset < self.text.len() && self.text[self.offset] == ' ' {
+            self.offset += 1;
+        }
+        if self.offset >= self.text.len() {
+            self.offset = 0;
+        }
+        self.chunk_start = self.offset;
+        let mut end = (self.offset + CHUNK_CHARS).min(self.text.len());
+        while end < self.text.len() && self.text[end] != ' ' {
+            end += 1;
+        }
+        self.text[self.offset..end].iter().collect()
+    }
+
+    // move the bookmark past the chars typed from the current chunk and save it
+    pub fn advance(&mut self, chars_typed: usize) -> io::Result<()> {
+        self.move_bookmark(chars_typed);
+
+        let mut bookmarks = load_bookmarks()?;
+        bookmarks.retain(|(hash, _)| *hash != self.hash);
+        bookmarks.push((self.hash.clone(), self.offset));
+        let content: String = bookmarks
+            .iter()
+            .map(|(hash, offset)| format!("{} {}\n", hash, offset))
+            .collect();
+        storage::write_data_file(BOOKMARKS_FILE, &content)
+    }
+
+    fn move_bookmark(&mut self, chars_typed: usize) {
+        self.offset = self
+            .offset
+            .max(self.chunk_start + chars_typed)
+            .min(self.text.len());
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn progress(&self) -> f64 {
+        self.offset as f64 / self.text.len() as f64 * 100.
+    }
+}
+
+fn load_bookmarks() -> io::Result<Vec<(String, usize)>> {
+    Ok(storage::read_data_file(BOOKMARKS_FILE)?
+        .lines()
+        .filter_map(|line| {
+            let (hash, offset) = line.split_once(' ')?;
+            Some((hash.to_string(), offset.parse().ok()?))
+        })
+        .collect())
+}
+
+// keep only the prose of a markdown file, dropping code blocks, markup and link targets
+fn strip_markdown(content: &str) -> String {
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || line.starts_with("<!--") || line.chars().all(|c| "-=*_|: ".contains(c))
+        {
+            // also skips horizontal rules, heading underlines and table separators
+            text.push('\n');
+            continue;
+        }
+
+        let line = line.trim_start_matches(['#', '>']).trim_start();
+        let line = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .or_else(|| line.strip_prefix("+ "))
+            .unwrap_or(line);
+
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' | '_' | '`' => {}
+                '!' if chars.peek() == Some(&'[') => {}
+                '[' => {
+                    // keep the link text, drop the target
+                    for c in chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                        if !"*_`".contains(c) {
+                            text.push(c);
+                        }
+                    }
+                    if chars.peek() == Some(&'(') {
+                        for c in chars.by_ref() {
+                            if c == ')' {
+                                break;
+                            }
+                        }
+                    }
+                }
+                _ => text.push(c),
+            }
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typing_test::{Rules, TypingTest};
+
+    fn book(text: &str) -> Book {
+        Book {
+            hash: String::new(),
+            text: text.chars().collect(),
+            offset: 0,
+            chunk_start: 0,
+        }
+    }
+
+    // the chars a test of the chunk counts as done once all of it is typed
+    fn type_all(chunk: &str) -> usize {
+        let mut test = TypingTest::new(chunk, Rules::default());
+        for c in chunk.trim().chars() {
+            test.type_key(c);
+        }
+        assert!(test.is_finished());
+        test.completed_chars()
+    }
+
+    #[test]
+    fn chunks_end_at_words() {
+        let mut book = book(&["ab"; 150].join(" "));
+        let chunk = book.next_chunk();
+        assert!(chunk.len() >= CHUNK_CHARS);
+        assert!(chunk.ends_with("ab"));
+        assert_eq!(book.text[chunk.len()], ' ');
+    }
+
+    #[test]
+    fn typing_every_chunk_gets_through_the_book() {
+        let mut book = book(&["ab"; 150].join(" "));
+        while book.progress() < 100. {
+            let chunk = book.next_chunk();
+            assert!(!chunk.starts_with(' '));
+            let offset = book.offset();
+            book.move_bookmark(type_all(&chunk));
+            assert!(book.offset() > offset);
+        }
+        assert_eq!(book.offset(), book.len());
+        // and then it starts over
+        assert!(book.next_chunk().starts_with("ab"));
+        assert_eq!(book.offset(), 0);
+    }
+
+    #[test]
+    fn retrying_a_chunk_moves_the_bookmark_once() {
+        let mut book = book("aa bb cc dd");
+        book.next_chunk();
+        book.move_bookmark(3);
+        book.move_bookmark(3);
+        assert_eq!(book.offset(), 3);
+        // a worse retry doesn't go back either
+        book.move_bookmark(0);
+        assert_eq!(book.offset(), 3);
+    }
+
+    #[test]
+    fn markdown_keeps_the_prose() {
+        let markdown = "# Title\n\
+            ===\n\
+            Some *bold* and `code` with a [link](http://example.com).\n\
+            ```\n\
+            let code = 1;\n\
+            ```\n\
+            - item\n\
+            > quoted\n\
+            <!-- comment -->\n\
+            ![image](image.png)\n";
+        let text = strip_markdown(markdown);
+        let words: Vec<&str> = text.split_whitespace().collect();
+        assert_eq!(
+            words,
+            [
+                "Title", "Some", "bold", "and", "code", "with", "a", "link.", "item", "quoted",
+                "image"
+            ]
+        );
+    }
+}
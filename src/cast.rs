@@ -0,0 +1,79 @@
+use crate::replay::Player;
+use crate::runs::Run;
+use crate::{draw_timer, TEST_DURATION};
+use crossterm::{queue, terminal};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// used when there is no terminal to take the size from
+const DEFAULT_SIZE: (u16, u16) = (100, 30);
+// how often the timer is drawn while no key is typed, as often as the test polls for keys
+const TICK: Duration = Duration::from_millis(100);
+
+// write a run as an asciicast v2 file, which is a json header line and a line with the output
+// for every change of the screen, at the time it happened
+pub fn export(run: Run, path: &Path) -> io::Result<()> {
+    let (width, height) = terminal::size().unwrap_or(DEFAULT_SIZE);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut cast = format!(
+        "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"title\": {}}}\n",
+        width,
+        height,
+        timestamp,
+        json_string(&format!("tylee, {:.2} wpm", run.wpm))
+    );
+
+    // the text changes with the keys and the timer all along
+    let mut times: Vec<Duration> = run.keys.iter().map(|&(time, _)| time).collect();
+    let mut player = Player::new(run);
+    let ticks = player.length.as_millis() / TICK.as_millis();
+    times.extend((0..=ticks as u32).map(|tick| TICK * tick));
+    times.push(player.length);
+    times.sort();
+    times.dedup();
+
+    let mut frame = Vec::new();
+    queue!(frame, terminal::Clear(terminal::ClearType::All))?;
+    player.test.draw(&mut frame, width, height, &[])?;
+    for time in times {
+        let keys_before = player.next_key;
+        player.seek(time);
+        if player.next_key != keys_before {
+            player.test.draw(&mut frame, width, height, &[])?;
+        }
+        let remaining_time = TEST_DURATION.saturating_sub(time);
+        draw_timer(&mut frame, &remaining_time, &TEST_DURATION, width)?;
+        push_event(&mut cast, time, &mut frame);
+    }
+
+    fs::write(path, cast)
+}
+
+// an output event with everything drawn since the last one
+fn push_event(cast: &mut String, time: Duration, frame: &mut Vec<u8>) {
+    cast.push_str(&format!(
+        "[{:.6}, \"o\", {}]\n",
+        time.as_secs_f64(),
+        json_string(&String::from_utf8_lossy(frame))
+    ));
+    frame.clear();
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,96 @@
+use crossterm::style::{Color, Stylize};
+
+// bit of every dot in a braille cell, by column and row from the top
+const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+const BRAILLE_BLANK: u32 = 0x2800;
+
+pub struct Series<'a> {
+    pub values: &'a [f64],
+    pub color: Color,
+}
+
+// a line chart of braille cells, width and height are in cells of two by four dots,
+// markers is one flag per value that shows up as a red x under the chart
+pub fn line_chart(series: &[Series], markers: &[bool], width: usize, height: usize) -> Vec<String> {
+    let max = series
+        .iter()
+        .flat_map(|series| series.values.iter())
+        .fold(1_f64, |max, &value| max.max(value));
+    // round the top up to a multiple of ten so the axis reads nicely
+    let max = (max / 10.).ceil() * 10.;
+
+    let dots_x = width * 2;
+    let dots_y = height * 4;
+    // bits and color of every cell
+    let mut cells = vec![vec![(0, Color::Reset); width]; height];
+
+    for series in series {
+        let points: Vec<(usize, usize)> = series
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let y = (value / max * (dots_y - 1) as f64).round() as usize;
+                (
+                    x_of(i, series.values.len(), dots_x),
+                    dots_y - 1 - y.min(dots_y - 1),
+                )
+            })
+            .collect();
+
+        let mut plot = |x: usize, y: usize| {
+            let cell = &mut cells[y / 4][x / 2];
+            cell.0 |= DOTS[x % 2][y % 4];
+            cell.1 = series.color;
+        };
+        if let [(x, y)] = points[..] {
+            plot(x, y);
+        }
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            let steps = x1.abs_diff(x0).max(y1.abs_diff(y0)).max(1);
+            for step in 0..=steps {
+                let t = step as f64 / steps as f64;
+                let x = x0 as f64 + (x1 as f64 - x0 as f64) * t;
+                let y = y0 as f64 + (y1 as f64 - y0 as f64) * t;
+                plot(x.round() as usize, y.round() as usize);
+            }
+        }
+    }
+
+    let mut lines = Vec::with_capacity(height + 1);
+    for (row, cells) in cells.iter().enumerate() {
+        let axis = match row {
+            0 => format!("{:>4} ┤", max),
+            _ if row == height - 1 => format!("{:>4} ┤", 0),
+            _ => "     │".to_string(),
+        };
+        let chart: String = cells
+            .iter()
+            .map(|&(bits, color)| {
+                let cell = char::from_u32(BRAILLE_BLANK + bits).unwrap();
+                cell.with(color).to_string()
+            })
+            .collect();
+        lines.push(axis + &chart);
+    }
+
+    let mut marker_row = vec![' '; width];
+    for (i, _) in markers.iter().enumerate().filter(|(_, &marker)| marker) {
+        marker_row[x_of(i, markers.len(), dots_x) / 2] = 'x';
+    }
+    lines.push(format!(
+        "      {}",
+        marker_row.iter().collect::<String>().red()
+    ));
+
+    lines
+}
+
+// dot column of the i-th of count values, spread over the whole width
+fn x_of(i: usize, count: usize, dots_x: usize) -> usize {
+    if count < 2 {
+        return 0;
+    }
+    i * (dots_x - 1) / (count - 1)
+}
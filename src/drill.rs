@@ -0,0 +1,258 @@
+use crate::history;
+use crate::stats;
+use crate::storage;
+use crate::typing_test::TypingTest;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DECK_FILE: &str = "drill";
+const DAY: u64 = 24 * 60 * 60;
+// reviews in a row at speed until a word leaves the deck
+const GRADUATION_STREAK: u32 = 3;
+// at speed means this share of the average raw wpm of the mode in the history, a bar that
+// stays put instead of the test's own average, which half of the words always miss
+const AT_SPEED_SHARE: f64 = 0.9;
+// the bar until there is a history to take the average from
+const DEFAULT_MIN_WPM: f64 = 30.;
+// SM-2 starts every word with this ease and never lets it drop below the minimum
+const START_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+
+// a mistyped word and when to practice it again
+struct Card {
+    ease: f64,
+    // seconds until the next review after the last one
+    interval: u64,
+    // unix timestamp
+    due: u64,
+    streak: u32,
+}
+
+enum Review {
+    Wrong,
+    Slow,
+    AtSpeed,
+}
+
+pub struct Deck {
+    cards: BTreeMap<String, Card>,
+}
+
+impl Deck {
+    pub fn load() -> io::Result<Deck> {
+        let cards = storage::read_data_file(DECK_FILE)?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let word = fields.next()?.to_string();
+                let card = Card {
+                    ease: fields.next()?.parse().ok()?,
+                    interval: fields.next()?.parse().ok()?,
+                    due: fields.next()?.parse().ok()?,
+                    streak: fields.next()?.parse().ok()?,
+                };
+                Some((word, card))
+            })
+            .collect();
+        Ok(Deck { cards })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content: String = self
+            .cards
+            .iter()
+            .map(|(word, card)| {
+                format!(
+                    "{}\t{:.2}\t{}\t{}\t{}\n",
+                    word, card.ease, card.interval, card.due, card.streak
+                )
+            })
+            .collect();
+        storage::write_data_file(DECK_FILE, &content)
+    }
+
+    // the words to practice now, the longest overdue first
+    pub fn due(&self) -> Vec<String> {
+        let now = now();
+        let mut due: Vec<(&String, &Card)> = self
+            .cards
+            .iter()
+            .filter(|(_, card)| card.due <= now)
+            .collect();
+        due.sort_by_key(|(_, card)| card.due);
+        due.into_iter().map(|(word, _)| word.clone()).collect()
+    }
+
+    // put mistyped words into the deck and schedule the due words that were typed,
+    // a word counts as typed at speed if it was at least as fast as min_wpm
+    pub fn review(&mut self, test: &TypingTest, min_wpm: f64) {
+        let now = now();
+        let speeds: BTreeMap<String, f64> = stats::word_speeds(test).into_iter().collect();
+
+        // the worst attempt counts when a word came up more than once
+        let mut reviews: BTreeMap<String, Review> = BTreeMap::new();
+        for (_, word, correct) in test.finished_words() {
+            let word: String = word.iter().collect();
+            let review = match speeds.get(&word) {
+                _ if !correct => Review::Wrong,
+                Some(&wpm) if wpm >= min_wpm => Review::AtSpeed,
+                _ => Review::Slow,
+            };
+            let Some(word) = clean(&word) else {
+                continue;
+            };
+            let worst = match (reviews.remove(&word), review) {
+                (Some(Review::Wrong), _) | (_, Review::Wrong) => Review::Wrong,
+                (Some(Review::Slow), _) | (_, Review::Slow) => Review::Slow,
+                _ => Review::AtSpeed,
+            };
+            reviews.insert(word, worst);
+        }
+
+        for (word, review) in reviews {
+            match (self.cards.get_mut(&word), review) {
+                (Some(card), Review::Wrong) => {
+                    card.ease = (card.ease - 0.2).max(MIN_EASE);
+                    card.interval = 0;
+                    card.due = now;
+                    card.streak = 0;
+                }
+                (None, Review::Wrong) => {
+                    let card = Card {
+                        ease: START_EASE,
+                        interval: 0,
+                        due: now,
+                        streak: 0,
+                    };
+                    self.cards.insert(word, card);
+                }
+                // typing a word before it is due says nothing about remembering it
+                (Some(card), _) if card.due > now => {}
+                (Some(card), Review::Slow) => {
+                    card.ease = (card.ease - 0.15).max(MIN_EASE);
+                    card.interval = DAY;
+                    card.due = now + DAY;
+                }
+                (Some(card), Review::AtSpeed) => {
+                    card.streak += 1;
+                    card.interval = match card.streak {
+                        1 => DAY,
+                        2 => 6 * DAY,
+                        _ => (card.interval as f64 * card.ease) as u64,
+                    };
+                    card.due = now + card.interval;
+                    if card.streak >= GRADUATION_STREAK {
+                        self.cards.remove(&word);
+                    }
+                }
+                (None, _) => {}
+            }
+        }
+    }
+}
+
+// how fast a word of a mode has to be typed to count as at speed
+pub fn min_wpm(category: &str) -> io::Result<f64> {
+    let wpms: Vec<f64> = history::load()?
+        .into_iter()
+        .filter(|record| record.category == category && record.counts())
+        .map(|record| record.raw_wpm)
+        .collect();
+    if wpms.is_empty() {
+        return Ok(DEFAULT_MIN_WPM);
+    }
+    Ok(wpms.iter().sum::<f64>() / wpms.len() as f64 * AT_SPEED_SHARE)
+}
+
+// the due words spread out between the words of the filler text
+pub fn mix_in(due: &[String], filler: &str, rng: &mut impl Rng) -> String {
+    let mut words: Vec<&str> = filler.split_whitespace().collect();
+    words.extend(due.iter().map(String::as_str));
+    words.shuffle(rng);
+    words.join(" ")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// the word without the punctuation around it, which belongs to the sentence more than the word
+fn clean(word: &str) -> Option<String> {
+    let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+    (!word.is_empty()).then(|| word.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typing_test::Rules;
+    use std::time::Duration;
+
+    // a test typed at a key every 100ms
+    fn typed(text: &str, keys: &str) -> TypingTest {
+        let mut test = TypingTest::new(text, Rules::default());
+        for key in keys.chars() {
+            test.type_key(key);
+        }
+        for (i, keystroke) in test.keystrokes.iter_mut().enumerate() {
+            keystroke.time = Duration::from_millis(i as u64 * 100);
+        }
+        test
+    }
+
+    fn deck() -> Deck {
+        Deck {
+            cards: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn mistyped_words_are_due_right_away() {
+        let mut deck = deck();
+        deck.review(&typed("ab cd, ef", "ab cx, ef"), 0.);
+        // without the punctuation
+        assert_eq!(deck.due(), ["cd"]);
+        assert_eq!(deck.cards["cd"].streak, 0);
+    }
+
+    #[test]
+    fn the_worst_attempt_counts() {
+        let mut deck = deck();
+        deck.review(&typed("ab ab", "ab ax"), 0.);
+        assert_eq!(deck.due(), ["ab"]);
+    }
+
+    #[test]
+    fn words_graduate_at_speed() {
+        let mut deck = deck();
+        deck.review(&typed("ab cd", "ab cx"), 0.);
+        for streak in 1..GRADUATION_STREAK {
+            deck.review(&typed("ab cd", "ab cd"), 0.);
+            let card = &deck.cards["cd"];
+            assert_eq!(card.streak, streak);
+            assert!(card.due > now());
+            // typed again before it is due, which changes nothing
+            deck.review(&typed("ab cd", "ab cd"), 0.);
+            assert_eq!(deck.cards["cd"].streak, streak);
+            deck.cards.get_mut("cd").unwrap().due = 0;
+        }
+        deck.review(&typed("ab cd", "ab cd"), 0.);
+        assert!(deck.cards.is_empty());
+    }
+
+    #[test]
+    fn slow_words_come_back_tomorrow() {
+        let mut deck = deck();
+        deck.review(&typed("ab cd", "ab cx"), 0.);
+        deck.review(&typed("ab cd", "ab cd"), 1000.);
+        let card = &deck.cards["cd"];
+        assert_eq!((card.streak, card.interval), (0, DAY));
+        assert!(card.ease < START_EASE);
+    }
+}
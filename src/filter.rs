@@ -0,0 +1,146 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use regex::Regex;
+
+const VOWELS: &str = "aeiouy";
+// length of made up words when no lengths are asked for
+const SYNTHETIC_LENGTH: (usize, usize) = (3, 7);
+// made up words can miss the regexes, give up on them after this many tries
+const SYNTHETIC_TRIES: usize = 100;
+
+// which words generated text may contain
+#[derive(Default)]
+pub struct WordFilter {
+    pub letters: Option<Vec<char>>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub include: Option<Regex>,
+    pub exclude: Option<Regex>,
+}
+
+impl WordFilter {
+    pub fn matches(&self, word: &str) -> bool {
+        let length = word.chars().count();
+        self.letters
+            .as_ref()
+            .is_none_or(|letters| word.chars().all(|c| letters.contains(&c)))
+            && self.min_length.is_none_or(|min| length >= min)
+            && self.max_length.is_none_or(|max| length <= max)
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|include| include.is_match(word))
+            && self
+                .exclude
+                .as_ref()
+                .is_none_or(|exclude| !exclude.is_match(word))
+    }
+
+    // a made up word of the allowed letters that takes turns between consonants and vowels,
+    // so it can be read out loud, none if every try missed the filter
+    pub fn synthetic_word(&self, rng: &mut impl Rng) -> Option<String> {
+        let letters: Vec<char> = match &self.letters {
+            Some(letters) => letters.clone(),
+            None => ('a'..='z').collect(),
+        };
+        let (vowels, consonants): (Vec<char>, Vec<char>) = letters
+            .iter()
+            .filter(|c| c.is_alphabetic())
+            .partition(|c| VOWELS.contains(c.to_ascii_lowercase()));
+        let min = self.min_length.unwrap_or(SYNTHETIC_LENGTH.0).max(1);
+        let max = self
+            .max_length
+            .unwrap_or(SYNTHETIC_LENGTH.1.max(min))
+            .max(min);
+
+        for _ in 0..SYNTHETIC_TRIES {
+            let length = rng.gen_range(min..=max);
+            let mut vowel = rng.gen_bool(0.5);
+            let word: String = (0..length)
+                .map(|_| {
+                    vowel = !vowel;
+                    // with only one kind of letter allowed it has to do for both
+                    let pick = match (vowel, vowels.is_empty(), consonants.is_empty()) {
+                        (true, false, _) | (false, false, true) => &vowels,
+                        (false, _, false) | (true, true, false) => &consonants,
+                        // no letters at all, only other chars
+                        _ => &letters,
+                    };
+                    *pick.choose(rng).unwrap()
+                })
+                .collect();
+            if self.matches(&word) {
+                return Some(word);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn matching() {
+        let filter = WordFilter {
+            letters: Some("abc".chars().collect()),
+            min_length: Some(2),
+            max_length: Some(3),
+            include: Some(Regex::new("^a").unwrap()),
+            exclude: Some(Regex::new("c$").unwrap()),
+        };
+        assert!(filter.matches("ab"));
+        assert!(filter.matches("aba"));
+        assert!(!filter.matches("a"));
+        assert!(!filter.matches("abab"));
+        assert!(!filter.matches("ad"));
+        assert!(!filter.matches("ba"));
+        assert!(!filter.matches("abc"));
+        assert!(WordFilter::default().matches("anything"));
+    }
+
+    #[test]
+    fn synthetic_words_take_turns() {
+        let filter = WordFilter {
+            letters: Some("aeiszt".chars().collect()),
+            min_length: Some(4),
+            max_length: Some(6),
+            ..WordFilter::default()
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let word = filter.synthetic_word(&mut rng).unwrap();
+            assert!(filter.matches(&word), "{}", word);
+            let vowels: Vec<bool> = word.chars().map(|c| VOWELS.contains(c)).collect();
+            assert!(vowels.windows(2).all(|pair| pair[0] != pair[1]), "{}", word);
+        }
+    }
+
+    #[test]
+    fn synthetic_words_with_one_kind_of_letter() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for letters in ["st", "ae", "1-"] {
+            let filter = WordFilter {
+                letters: Some(letters.chars().collect()),
+                ..WordFilter::default()
+            };
+            let word = filter.synthetic_word(&mut rng).unwrap();
+            assert!(filter.matches(&word), "{}", word);
+            assert!(word.chars().count() >= SYNTHETIC_LENGTH.0);
+        }
+    }
+
+    #[test]
+    fn synthetic_words_can_miss_the_filter() {
+        let filter = WordFilter {
+            letters: Some("ab".chars().collect()),
+            include: Some(Regex::new("c").unwrap()),
+            ..WordFilter::default()
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(filter.synthetic_word(&mut rng), None);
+    }
+}
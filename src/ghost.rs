@@ -0,0 +1,96 @@
+use crate::runs::Run;
+use crate::typing_test::{Rules, TypingTest};
+use std::time::Duration;
+
+// a saved run typing the same text along with the test, key for key as it was typed back then
+pub struct Ghost {
+    run: Run,
+    rules: Rules,
+    test: TypingTest,
+    // how many keys of the run the ghost has typed so far
+    next_key: usize,
+    // when the run got to the end of the text, if it did
+    finished: Option<Duration>,
+}
+
+impl Ghost {
+    // the keys are typed with the rules of the run, other rules could take them differently
+    pub fn new(run: Run) -> Ghost {
+        let rules = run.rules();
+        let mut test = TypingTest::new(&run.text, rules);
+        let mut finished = None;
+        for &(time, key) in &run.keys {
+            test.press(key);
+            if test.is_finished() {
+                finished = Some(time);
+                break;
+            }
+        }
+
+        Ghost {
+            test: TypingTest::new(&run.text, rules),
+            run,
+            rules,
+            next_key: 0,
+            finished,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.run.text
+    }
+
+    pub fn wpm(&self) -> f64 {
+        self.run.wpm
+    }
+
+    // back to the start for another race
+    pub fn reset(&mut self) {
+        self.test = TypingTest::new(&self.run.text, self.rules);
+        self.next_key = 0;
+    }
+
+    // type the keys the run had typed at this point of the race
+    pub fn catch_up(&mut self, elapsed: Duration) {
+        while let Some(&(time, key)) = self.run.keys.get(self.next_key) {
+            if time > elapsed {
+                break;
+            }
+            self.test.press(key);
+            self.next_key += 1;
+        }
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        self.test.position()
+    }
+
+    pub fn margin(&mut self, test: &TypingTest) -> String {
+        self.catch_up(test.elapsed().unwrap_or_default());
+        margin(test, "the ghost", self.test.chars_typed(), self.finished)
+    }
+}
+
+// how far ahead of a rival the test ended, in time if both got to the end of the text
+// and in chars otherwise
+pub fn margin(
+    test: &TypingTest,
+    rival: &str,
+    rival_chars: usize,
+    rival_finished: Option<Duration>,
+) -> String {
+    let elapsed = test.elapsed().unwrap_or_default();
+    if let (true, Some(finished)) = (test.is_finished(), rival_finished) {
+        let margin = finished.as_secs_f64() - elapsed.as_secs_f64();
+        return match margin >= 0. {
+            true => format!("{:.2}s ahead of {}", margin, rival),
+            false => format!("{:.2}s behind {}", -margin, rival),
+        };
+    }
+    let margin = test.chars_typed() as isize - rival_chars as isize;
+    match margin {
+        0 => format!("even with {}", rival),
+        1.. => format!("{} chars ahead of {}", margin, rival),
+        _ => format!("{} chars behind {}", -margin, rival),
+    }
+}
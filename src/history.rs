@@ -0,0 +1,356 @@
+use crate::keyboard::{Keyboard, Metric};
+use crate::stats::{self, KeyStats};
+use crate::storage;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::time::Duration;
+
+const HISTORY_FILE: &str = "history";
+// ngrams seen fewer times over the whole history are too noisy to judge
+const MIN_NGRAM_COUNT: usize = 3;
+
+// one finished test, stored as a line of tab separated key=value pairs
+// so fields can be added without breaking older history files
+#[derive(Default)]
+pub struct Record {
+    // unix time in seconds
+    pub timestamp: u64,
+    pub category: String,
+    pub seconds: f64,
+    pub wpm: f64,
+    pub raw_wpm: f64,
+    pub accuracy: f64,
+    pub errors: usize,
+    pub incorrect_chars: usize,
+    pub extra_chars: usize,
+    pub missed_chars: usize,
+    pub consistency: f64,
+    pub burst_wpm: f64,
+    pub slowest_words: Vec<(String, f64)>,
+    pub keys: BTreeMap<char, KeyStats>,
+    pub bigrams: BTreeMap<String, KeyStats>,
+    pub trigrams: BTreeMap<String, KeyStats>,
+    // only set in modes that can fail
+    pub passed: Option<bool>,
+    pub failure: Option<String>,
+    // ended with esc
+    pub aborted: bool,
+}
+
+impl Record {
+    // whether the test can be a best or go into an average speed,
+    // failed and aborted ones can't
+    pub fn counts(&self) -> bool {
+        self.passed != Some(false) && !self.aborted
+    }
+
+    fn to_line(&self) -> String {
+        let mut fields = vec![
+            format!("timestamp={}", self.timestamp),
+            format!("category={}", self.category),
+            format!("seconds={:.3}", self.seconds),
+            format!("wpm={:.2}", self.wpm),
+            format!("raw_wpm={:.2}", self.raw_wpm),
+            format!("accuracy={:.2}", self.accuracy),
+            format!("errors={}", self.errors),
+            format!("incorrect_chars={}", self.incorrect_chars),
+            format!("extra_chars={}", self.extra_chars),
+            format!("missed_chars={}", self.missed_chars),
+            format!("consistency={:.2}", self.consistency),
+            format!("burst_wpm={:.2}", self.burst_wpm),
+            // words never contain whitespace and the wpm never contains a colon
+            format!(
+                "slowest_words={}",
+                self.slowest_words
+                    .iter()
+                    .map(|(word, wpm)| format!("{}:{:.2}", word, wpm))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            // chars as their code point, so spaces and colons need no escaping
+            format!(
+                "keys={}",
+                self.keys
+                    .iter()
+                    .map(|(c, stats)| format!(
+                        "{}:{}:{}:{}:{}",
+                        *c as u32,
+                        stats.presses,
+                        stats.errors,
+                        stats.latency.as_millis(),
+                        stats.timed
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            format!("bigrams={}", ngrams_to_value(&self.bigrams)),
+            format!("trigrams={}", ngrams_to_value(&self.trigrams)),
+        ];
+        if let Some(passed) = self.passed {
+            fields.push(format!("passed={}", passed));
+        }
+        if let Some(failure) = &self.failure {
+            fields.push(format!("failure={}", failure));
+        }
+        if self.aborted {
+            fields.push("aborted=true".to_string());
+        }
+        fields.join("\t")
+    }
+
+    fn from_line(line: &str) -> Record {
+        let mut record = Record::default();
+        for (key, value) in line.split('\t').filter_map(|field| field.split_once('=')) {
+            match key {
+                "timestamp" => record.timestamp = value.parse().unwrap_or_default(),
+                "category" => record.category = value.to_string(),
+                "seconds" => record.seconds = value.parse().unwrap_or_default(),
+                "wpm" => record.wpm = value.parse().unwrap_or_default(),
+                "raw_wpm" => record.raw_wpm = value.parse().unwrap_or_default(),
+                "accuracy" => record.accuracy = value.parse().unwrap_or_default(),
+                "errors" => record.errors = value.parse().unwrap_or_default(),
+                "incorrect_chars" => record.incorrect_chars = value.parse().unwrap_or_default(),
+                "extra_chars" => record.extra_chars = value.parse().unwrap_or_default(),
+                "missed_chars" => record.missed_chars = value.parse().unwrap_or_default(),
+                "consistency" => record.consistency = value.parse().unwrap_or_default(),
+                "burst_wpm" => record.burst_wpm = value.parse().unwrap_or_default(),
+                "slowest_words" => {
+                    record.slowest_words = value
+                        .split(' ')
+                        .filter_map(|entry| {
+                            let (word, wpm) = entry.rsplit_once(':')?;
+                            Some((word.to_string(), wpm.parse().ok()?))
+                        })
+                        .collect()
+                }
+                "keys" => {
+                    record.keys = value
+                        .split(' ')
+                        .filter_map(|entry| {
+                            let fields: Vec<u64> = entry
+                                .split(':')
+                                .map(|field| field.parse().ok())
+                                .collect::<Option<_>>()?;
+                            let [c, presses, errors, latency, timed] = fields[..] else {
+                                return None;
+                            };
+                            let stats = KeyStats {
+                                presses: presses as usize,
+                                errors: errors as usize,
+                                latency: Duration::from_millis(latency),
+                                timed: timed as usize,
+                            };
+                            Some((char::from_u32(c as u32)?, stats))
+                        })
+                        .collect()
+                }
+                "bigrams" => record.bigrams = ngrams_from_value(value),
+                "trigrams" => record.trigrams = ngrams_from_value(value),
+                "passed" => record.passed = value.parse().ok(),
+                "failure" => record.failure = Some(value.to_string()),
+                "aborted" => record.aborted = value.parse().unwrap_or_default(),
+                // written by a newer version
+                _ => {}
+            }
+        }
+        record
+    }
+}
+
+// ngrams never contain whitespace, colons in them are fine
+// because the numbers are split off from the right
+fn ngrams_to_value(ngrams: &BTreeMap<String, KeyStats>) -> String {
+    ngrams
+        .iter()
+        .map(|(ngram, stats)| {
+            format!(
+                "{}:{}:{}:{}:{}",
+                ngram,
+                stats.presses,
+                stats.errors,
+                stats.latency.as_millis(),
+                stats.timed
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn ngrams_from_value(value: &str) -> BTreeMap<String, KeyStats> {
+    value
+        .split(' ')
+        .filter_map(|entry| {
+            let mut fields = entry.rsplitn(5, ':');
+            let timed = fields.next()?.parse().ok()?;
+            let latency = fields.next()?.parse().ok()?;
+            let errors = fields.next()?.parse().ok()?;
+            let presses = fields.next()?.parse().ok()?;
+            let stats = KeyStats {
+                presses,
+                errors,
+                latency: Duration::from_millis(latency),
+                timed,
+            };
+            Some((fields.next()?.to_string(), stats))
+        })
+        .collect()
+}
+
+pub fn append(record: &Record) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(storage::data_dir()?.join(HISTORY_FILE))?;
+    writeln!(file, "{}", record.to_line())
+}
+
+pub fn load() -> io::Result<Vec<Record>> {
+    Ok(storage::read_data_file(HISTORY_FILE)?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(Record::from_line)
+        .collect())
+}
+
+// averages of every category and the keys over the whole history
+pub fn print_stats(layout: &Keyboard) -> io::Result<()> {
+    let records = load()?;
+    if records.is_empty() {
+        println!("no tests in the history yet");
+        return Ok(());
+    }
+
+    let mut categories: BTreeMap<&str, Vec<&Record>> = BTreeMap::new();
+    // aborted tests only count for the keys
+    for record in records.iter().filter(|record| !record.aborted) {
+        categories.entry(&record.category).or_default().push(record);
+    }
+
+    println!(
+        "{:<30} {:>6} {:>8} {:>8} {:>9} {:>12}",
+        "mode", "tests", "avg wpm", "best wpm", "accuracy", "consistency"
+    );
+    for (category, records) in &categories {
+        // failed tests are counted, but left out of the averages and the best
+        let counted: Vec<&Record> = records
+            .iter()
+            .copied()
+            .filter(|record| record.counts())
+            .collect();
+        if counted.is_empty() {
+            println!(
+                "{:<30} {:>6} {:>8} {:>8} {:>9} {:>12}",
+                category,
+                records.len(),
+                "-",
+                "-",
+                "-",
+                "-"
+            );
+            continue;
+        }
+        let count = counted.len() as f64;
+        let average =
+            |field: fn(&Record) -> f64| counted.iter().map(|r| field(r)).sum::<f64>() / count;
+        println!(
+            "{:<30} {:>6} {:>8.2} {:>8.2} {:>8.2}% {:>11.2}%",
+            category,
+            records.len(),
+            average(|record| record.wpm),
+            counted.iter().map(|record| record.wpm).fold(0., f64::max),
+            average(|record| record.accuracy),
+            average(|record| record.consistency),
+        );
+    }
+
+    let mut keys: BTreeMap<char, KeyStats> = BTreeMap::new();
+    for record in &records {
+        for (c, stats) in &record.keys {
+            keys.entry(*c).or_default().add(stats);
+        }
+    }
+    for metric in [Metric::Errors, Metric::Latency] {
+        println!();
+        for line in layout.heatmap(&keys, metric) {
+            println!("{}", line);
+        }
+    }
+
+    let mut bigrams: BTreeMap<String, KeyStats> = BTreeMap::new();
+    let mut trigrams: BTreeMap<String, KeyStats> = BTreeMap::new();
+    for record in &records {
+        for (ngrams, record_ngrams) in [
+            (&mut bigrams, &record.bigrams),
+            (&mut trigrams, &record.trigrams),
+        ] {
+            for (ngram, stats) in record_ngrams {
+                ngrams.entry(ngram.clone()).or_default().add(stats);
+            }
+        }
+    }
+    println!();
+    for line in stats::ngram_lines("bigrams", &bigrams, MIN_NGRAM_COUNT)
+        .into_iter()
+        .chain(stats::ngram_lines("trigrams", &trigrams, MIN_NGRAM_COUNT))
+    {
+        println!("{}", line);
+    }
+    println!();
+    for line in layout.finger_lines(&keys, &bigrams) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let stats = KeyStats {
+            presses: 4,
+            errors: 1,
+            latency: Duration::from_millis(830),
+            timed: 3,
+        };
+        let record = Record {
+            timestamp: 1_700_000_000,
+            category: "strict+accuracy-95".to_string(),
+            seconds: 10.5,
+            wpm: 61.25,
+            raw_wpm: 70.5,
+            accuracy: 96.75,
+            errors: 3,
+            incorrect_chars: 2,
+            extra_chars: 1,
+            missed_chars: 4,
+            consistency: 81.5,
+            burst_wpm: 110.25,
+            slowest_words: vec![("a:b".to_string(), 20.5), ("cd".to_string(), 30.)],
+            keys: BTreeMap::from([(' ', stats), (':', stats), ('é', stats)]),
+            bigrams: BTreeMap::from([("a:".to_string(), stats)]),
+            trigrams: BTreeMap::from([("a:b".to_string(), stats)]),
+            passed: Some(false),
+            failure: Some("accuracy".to_string()),
+            aborted: true,
+        };
+        let line = record.to_line();
+        let parsed = Record::from_line(&line);
+        assert_eq!(parsed.to_line(), line);
+        assert_eq!(parsed.keys[&':'].latency, Duration::from_millis(830));
+        assert_eq!(parsed.slowest_words[0].0, "a:b");
+        assert!(!parsed.counts());
+    }
+
+    #[test]
+    fn records_skip_unknown_fields() {
+        let record = Record::from_line("wpm=50.00\tfrom_the_future=1\tpassed=true");
+        assert_eq!(record.wpm, 50.);
+        assert_eq!(record.passed, Some(true));
+        assert!(!record.aborted);
+        assert!(record.counts());
+    }
+}
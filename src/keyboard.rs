@@ -0,0 +1,581 @@
+use crate::stats::KeyStats;
+use crossterm::style::{Color, Stylize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 256 color palette from green over yellow to red
+const HEAT: [u8; 11] = [46, 82, 118, 154, 190, 226, 220, 214, 208, 202, 196];
+const WORST_KEYS: usize = 5;
+
+#[derive(Clone, Copy)]
+pub enum Metric {
+    // share of presses that were wrong
+    Errors,
+    // average time it took to get to the key
+    Latency,
+}
+
+// how far each row of a standard keyboard is indented
+const INDENTS: [usize; 4] = [0, 2, 3, 5];
+const WORST_SAME_FINGER_BIGRAMS: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    // both thumbs, they only press space
+    Thumbs,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+const FINGERS: [Finger; 9] = [
+    Finger::LeftPinky,
+    Finger::LeftRing,
+    Finger::LeftMiddle,
+    Finger::LeftIndex,
+    Finger::Thumbs,
+    Finger::RightIndex,
+    Finger::RightMiddle,
+    Finger::RightRing,
+    Finger::RightPinky,
+];
+
+// last column of the left pinky, ring, middle and index finger in every row of a standard
+// keyboard, the right hand mirrors it with the pinky taking whatever is left over
+const FINGER_COLUMNS: [[usize; 4]; 4] = [[1, 2, 3, 5], [0, 1, 2, 4], [0, 1, 2, 4], [0, 1, 2, 4]];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Hand {
+    Left,
+    Right,
+}
+
+impl Finger {
+    fn name(self) -> &'static str {
+        match self {
+            Finger::LeftPinky => "left pinky",
+            Finger::LeftRing => "left ring",
+            Finger::LeftMiddle => "left middle",
+            Finger::LeftIndex => "left index",
+            Finger::Thumbs => "thumbs",
+            Finger::RightIndex => "right index",
+            Finger::RightMiddle => "right middle",
+            Finger::RightRing => "right ring",
+            Finger::RightPinky => "right pinky",
+        }
+    }
+
+    fn hand(self) -> Option<Hand> {
+        match self {
+            Finger::Thumbs => None,
+            _ if self < Finger::Thumbs => Some(Hand::Left),
+            _ => Some(Hand::Right),
+        }
+    }
+}
+
+pub struct Keyboard {
+    // keys of every row as (unshifted, shifted), with how far the row is indented
+    rows: Vec<(usize, Vec<(char, char)>)>,
+}
+
+pub fn qwerty() -> Keyboard {
+    keyboard([
+        ("`1234567890-=", "~!@#$%^&*()_+"),
+        ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+        ("asdfghjkl;'", "ASDFGHJKL:\""),
+        ("zxcvbnm,./", "ZXCVBNM<>?"),
+    ])
+}
+
+pub fn dvorak() -> Keyboard {
+    keyboard([
+        ("`1234567890[]", "~!@#$%^&*(){}"),
+        ("',.pyfgcrl/=\\", "\"<>PYFGCRL?+|"),
+        ("aoeuidhtns-", "AOEUIDHTNS_"),
+        (";qjkxbmwvz", ":QJKXBMWVZ"),
+    ])
+}
+
+pub fn colemak() -> Keyboard {
+    keyboard([
+        ("`1234567890-=", "~!@#$%^&*()_+"),
+        ("qwfpgjluy;[]\\", "QWFPGJLUY:{}|"),
+        ("arstdhneio'", "ARSTDHNEIO\""),
+        ("zxcvbkm,./", "ZXCVBKM<>?"),
+    ])
+}
+
+pub fn colemak_dh() -> Keyboard {
+    keyboard([
+        ("`1234567890-=", "~!@#$%^&*()_+"),
+        ("qwfpbjluy;[]\\", "QWFPBJLUY:{}|"),
+        ("arstgmneio'", "ARSTGMNEIO\""),
+        ("zxcdvkh,./", "ZXCDVKH<>?"),
+    ])
+}
+
+pub fn workman() -> Keyboard {
+    keyboard([
+        ("`1234567890-=", "~!@#$%^&*()_+"),
+        ("qdrwbjfup;[]\\", "QDRWBJFUP:{}|"),
+        ("ashtgyneoi'", "ASHTGYNEOI\""),
+        ("zxmcvkl,./", "ZXMCVKL<>?"),
+    ])
+}
+
+fn keyboard(rows: [(&str, &str); 4]) -> Keyboard {
+    Keyboard {
+        rows: INDENTS
+            .iter()
+            .zip(rows)
+            .map(|(&indent, (unshifted, shifted))| (indent, row(unshifted, shifted)))
+            .collect(),
+    }
+}
+
+fn row(unshifted: &str, shifted: &str) -> Vec<(char, char)> {
+    unshifted.chars().zip(shifted.chars()).collect()
+}
+
+// one of the layouts above by name, or else a file with the four rows of a layout,
+// each as its unshifted and its shifted keys separated by a space, like
+// `1234567890-= ~!@#$%^&*()_+
+pub fn layout(name: &str) -> io::Result<Keyboard> {
+    match name {
+        "qwerty" => return Ok(qwerty()),
+        "dvorak" => return Ok(dvorak()),
+        "colemak" => return Ok(colemak()),
+        "colemak-dh" => return Ok(colemak_dh()),
+        "workman" => return Ok(workman()),
+        _ => {}
+    }
+
+    let content = fs::read_to_string(Path::new(name)).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "unknown layout {}, expected qwerty, dvorak, colemak, colemak-dh, workman or a layout file",
+                name
+            ),
+        ),
+        _ => err,
+    })?;
+    let invalid = |message: String| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", name, message))
+    };
+    let lines: Vec<&str> = content.lines().filter(|line| !line.is_empty()).collect();
+    let qwerty = qwerty();
+    if lines.len() != qwerty.rows.len() {
+        return Err(invalid(format!("expected {} rows", qwerty.rows.len())));
+    }
+    let mut rows = Vec::new();
+    for (i, (line, (indent, qwerty_row))) in lines.iter().zip(&qwerty.rows).enumerate() {
+        let (unshifted, shifted) = line
+            .split_once(' ')
+            .ok_or_else(|| invalid(format!("row {} has no shifted keys", i + 1)))?;
+        // every key has to be there so any key typed can be remapped
+        if unshifted.chars().count() != qwerty_row.len()
+            || shifted.chars().count() != qwerty_row.len()
+        {
+            return Err(invalid(format!(
+                "row {} needs {} keys",
+                i + 1,
+                qwerty_row.len()
+            )));
+        }
+        rows.push((*indent, row(unshifted, shifted)));
+    }
+    Ok(Keyboard { rows })
+}
+
+impl Keyboard {
+    // the keys of a row, counted from the number row
+    pub fn keys(&self, row: usize) -> &[(char, char)] {
+        &self.rows[row].1
+    }
+
+    // row and column of the key a char is on
+    fn key(&self, c: char) -> Option<(usize, usize)> {
+        self.rows.iter().enumerate().find_map(|(row, (_, keys))| {
+            let column = keys
+                .iter()
+                .position(|&(unshifted, shifted)| c == unshifted || c == shifted)?;
+            Some((row, column))
+        })
+    }
+
+    // the finger that types a char when touch typing
+    pub fn finger(&self, c: char) -> Option<Finger> {
+        if c == ' ' {
+            return Some(Finger::Thumbs);
+        }
+        let (row, column) = self.key(c)?;
+        let left = FINGER_COLUMNS[row].iter().position(|&last| column <= last);
+        let right_start = FINGER_COLUMNS[row][3] + 1;
+        Some(match left {
+            Some(0) => Finger::LeftPinky,
+            Some(1) => Finger::LeftRing,
+            Some(2) => Finger::LeftMiddle,
+            Some(_) => Finger::LeftIndex,
+            None => match column - right_start {
+                0 | 1 => Finger::RightIndex,
+                2 => Finger::RightMiddle,
+                3 => Finger::RightRing,
+                _ => Finger::RightPinky,
+            },
+        })
+    }
+
+    // load, accuracy and speed of every finger and hand, how often a finger has to type two
+    // keys in a row and how often the hands take turns
+    pub fn finger_lines(
+        &self,
+        keys: &BTreeMap<char, KeyStats>,
+        bigrams: &BTreeMap<String, KeyStats>,
+    ) -> Vec<String> {
+        let mut fingers: BTreeMap<Finger, KeyStats> = BTreeMap::new();
+        for (&c, stats) in keys {
+            if let Some(finger) = self.finger(c) {
+                fingers.entry(finger).or_default().add(stats);
+            }
+        }
+        let mut hands = [KeyStats::default(); 2];
+        for (finger, stats) in &fingers {
+            match finger.hand() {
+                Some(Hand::Left) => hands[0].add(stats),
+                Some(Hand::Right) => hands[1].add(stats),
+                None => {}
+            }
+        }
+        let total = fingers
+            .values()
+            .map(|stats| stats.presses)
+            .sum::<usize>()
+            .max(1);
+        let line = |name: &str, stats: &KeyStats| {
+            let (accuracy, time) = match stats.presses {
+                0 => ("-".to_string(), "-".to_string()),
+                _ => (
+                    percent(1. - stats.error_rate()),
+                    stats.average_latency().map_or("-".to_string(), |latency| {
+                        milliseconds(latency.as_secs_f64() * 1000.)
+                    }),
+                ),
+            };
+            format!(
+                "{:<13}{:>5}{:>10}{:>8}",
+                name,
+                percent(stats.presses as f64 / total as f64),
+                accuracy,
+                time
+            )
+        };
+
+        let mut lines = vec![format!(
+            "{:<13}{:>5}{:>10}{:>8}",
+            "finger", "load", "accuracy", "time"
+        )];
+        for finger in FINGERS {
+            let stats = fingers.get(&finger).copied().unwrap_or_default();
+            lines.push(line(finger.name(), &stats));
+        }
+        lines.push(line("left hand", &hands[0]));
+        lines.push(line("right hand", &hands[1]));
+
+        let mut pairs = 0;
+        let mut alternations = 0;
+        let mut same_finger: Vec<(&String, usize)> = Vec::new();
+        for (bigram, stats) in bigrams {
+            let mut chars = bigram.chars();
+            let (Some(first), Some(second)) = (chars.next(), chars.next()) else {
+                continue;
+            };
+            let (Some(first_finger), Some(second_finger)) =
+                (self.finger(first), self.finger(second))
+            else {
+                continue;
+            };
+            pairs += stats.presses;
+            if first_finger == second_finger && self.key(first) != self.key(second) {
+                same_finger.push((bigram, stats.presses));
+            }
+            if first_finger.hand().is_some() && first_finger.hand() != second_finger.hand() {
+                alternations += stats.presses;
+            }
+        }
+        same_finger.sort_by_key(|&(_, presses)| std::cmp::Reverse(presses));
+        let pairs = pairs.max(1) as f64;
+        lines.push(format!(
+            "same finger bigrams: {}, most: {}",
+            percent(
+                same_finger
+                    .iter()
+                    .map(|(_, presses)| presses)
+                    .sum::<usize>() as f64
+                    / pairs
+            ),
+            same_finger
+                .iter()
+                .take(WORST_SAME_FINGER_BIGRAMS)
+                .map(|(bigram, presses)| format!("{} {}", bigram, presses))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        lines.push(format!(
+            "hand alternation: {}",
+            percent(alternations as f64 / pairs)
+        ));
+
+        lines
+    }
+
+    // columns the widest row takes up when drawn
+    pub fn width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|(indent, keys)| indent + keys.len() * 4)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // the keyboard with the key to press next and the shift key to hold with it lit up,
+    // and the key pressed by mistake in red
+    pub fn diagram(&self, next: char, wrong: Option<char>) -> Vec<String> {
+        let style = |label: String, chars: &[char]| {
+            if chars.contains(&next) {
+                label.black().on_green().to_string()
+            } else if wrong.is_some_and(|wrong| chars.contains(&wrong)) {
+                label.white().on_red().to_string()
+            } else {
+                label.dark_grey().to_string()
+            }
+        };
+        // shift is held with the other hand than the one typing the key
+        let shifted = self.rows.iter().any(|(_, keys)| {
+            keys.iter()
+                .any(|&(unshifted, shifted)| next == shifted && next != unshifted)
+        });
+        let shift_hand = match self.finger(next).and_then(Finger::hand) {
+            Some(Hand::Left) if shifted => Some(Hand::Right),
+            Some(Hand::Right) if shifted => Some(Hand::Left),
+            _ => None,
+        };
+        let shift = |hand: Hand| match shift_hand == Some(hand) {
+            true => "shift".black().on_green().to_string(),
+            false => "shift".dark_grey().to_string(),
+        };
+
+        let mut lines = Vec::new();
+        for (row, (indent, keys)) in self.rows.iter().enumerate() {
+            // the shift keys are on both ends of the last row
+            let last_row = row == self.rows.len() - 1;
+            let mut line = match last_row {
+                true => shift(Hand::Left) + &" ".repeat(indent.saturating_sub(5)),
+                false => " ".repeat(*indent),
+            };
+            for &(unshifted, shifted) in keys {
+                line.push_str(&style(format!(" {} ", unshifted), &[unshifted, shifted]));
+                line.push(' ');
+            }
+            if last_row {
+                line.push_str(&shift(Hand::Right));
+            }
+            lines.push(line);
+        }
+        lines.push(format!(
+            "{}{}",
+            " ".repeat(14),
+            style(format!("{:^23}", "space"), &[' '])
+        ));
+
+        lines
+    }
+
+    // what a char typed on the os layout is on this one, going by the key it is on
+    pub fn remap_from(&self, os: &Keyboard) -> BTreeMap<char, char> {
+        let mut remap = BTreeMap::new();
+        for ((_, os_row), (_, row)) in os.rows.iter().zip(&self.rows) {
+            for (&(os_unshifted, os_shifted), &(unshifted, shifted)) in os_row.iter().zip(row) {
+                remap.insert(os_unshifted, unshifted);
+                remap.insert(os_shifted, shifted);
+            }
+        }
+        remap.retain(|from, to| from != to);
+        remap
+    }
+
+    // the keyboard with every key colored by how bad it is, plus the worst keys by name
+    pub fn heatmap(&self, stats: &BTreeMap<char, KeyStats>, metric: Metric) -> Vec<String> {
+        let key_stats = |chars: &[char]| {
+            let mut key_stats = KeyStats::default();
+            for c in chars {
+                if let Some(stats) = stats.get(c) {
+                    key_stats.add(stats);
+                }
+            }
+            value(&key_stats, metric)
+        };
+
+        let mut keys: Vec<(char, Option<f64>)> = Vec::new();
+        for (_, row) in &self.rows {
+            for &(unshifted, shifted) in row {
+                keys.push((unshifted, key_stats(&[unshifted, shifted])));
+            }
+        }
+        let space = key_stats(&[' ']);
+
+        let values = keys.iter().filter_map(|(_, value)| *value).chain(space);
+        let (min, max) = match metric {
+            // a handful of errors shouldn't paint everything red
+            Metric::Errors => (0., values.fold(0.05, f64::max)),
+            Metric::Latency => values.fold((f64::MAX, 0_f64), |(min, max), value| {
+                (min.min(value), max.max(value))
+            }),
+        };
+        let shade = |label: String, value: Option<f64>| match value {
+            Some(value) => {
+                let heat = if max > min {
+                    (value - min) / (max - min)
+                } else {
+                    0.
+                };
+                let color = HEAT[(heat * (HEAT.len() - 1) as f64).round() as usize];
+                label.black().on(Color::AnsiValue(color)).to_string()
+            }
+            None => label.dark_grey().to_string(),
+        };
+
+        let mut lines = vec![match metric {
+            Metric::Errors => format!("errors per key, {} to {}", "0%".green(), percent(max).red()),
+            Metric::Latency if min <= max => format!(
+                "time per key, {} to {}",
+                milliseconds(min).green(),
+                milliseconds(max).red()
+            ),
+            Metric::Latency => "time per key".to_string(),
+        }];
+
+        let mut key_values = keys.iter();
+        for (indent, row) in &self.rows {
+            let mut line = " ".repeat(*indent);
+            for (label, value) in key_values.by_ref().take(row.len()) {
+                line.push_str(&shade(format!(" {} ", label), *value));
+                line.push(' ');
+            }
+            lines.push(line);
+        }
+        lines.push(format!(
+            "{}{}",
+            " ".repeat(14),
+            shade(format!("{:^23}", "space"), space)
+        ));
+
+        let mut worst: Vec<(char, f64)> = keys
+            .iter()
+            .filter_map(|&(label, value)| Some((label, value?)))
+            .chain(space.map(|value| ('␣', value)))
+            .filter(|&(_, value)| value > 0.)
+            .collect();
+        worst.sort_by(|a, b| b.1.total_cmp(&a.1));
+        lines.push(format!(
+            "worst: {}",
+            worst
+                .iter()
+                .take(WORST_KEYS)
+                .map(|&(label, value)| match metric {
+                    Metric::Errors => format!("{} {}", label, percent(value)),
+                    Metric::Latency => format!("{} {}", label, milliseconds(value)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+
+        lines
+    }
+}
+
+fn value(key_stats: &KeyStats, metric: Metric) -> Option<f64> {
+    match metric {
+        Metric::Errors => (key_stats.presses > 0).then(|| key_stats.error_rate()),
+        Metric::Latency => key_stats
+            .average_latency()
+            .map(|latency| latency.as_secs_f64() * 1000.),
+    }
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.0}%", rate * 100.)
+}
+
+fn milliseconds(milliseconds: f64) -> String {
+    format!("{:.0}ms", milliseconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remapping() {
+        let remap = dvorak().remap_from(&qwerty());
+        assert_eq!(remap[&'q'], '\'');
+        assert_eq!(remap[&'s'], 'o');
+        assert_eq!(remap[&'Q'], '"');
+        assert_eq!(remap[&'-'], '[');
+        // keys in the same place aren't remapped
+        assert!(!remap.contains_key(&'a'));
+        assert!(!remap.contains_key(&'1'));
+        assert!(qwerty().remap_from(&qwerty()).is_empty());
+
+        // typing every key of colemak on a qwerty keyboard gives every key of colemak
+        let colemak = colemak();
+        let remap = colemak.remap_from(&qwerty());
+        for row in 0..4 {
+            for (&(qwerty_key, _), &(colemak_key, _)) in
+                qwerty().keys(row).iter().zip(colemak.keys(row))
+            {
+                assert_eq!(*remap.get(&qwerty_key).unwrap_or(&qwerty_key), colemak_key);
+            }
+        }
+    }
+
+    #[test]
+    fn fingers() {
+        let finger = |keyboard: &Keyboard, c| keyboard.finger(c).map(Finger::name);
+        let qwerty = qwerty();
+        let home_row = [
+            ('a', "left pinky"),
+            ('s', "left ring"),
+            ('d', "left middle"),
+            ('f', "left index"),
+            ('g', "left index"),
+            ('h', "right index"),
+            ('j', "right index"),
+            ('k', "right middle"),
+            ('l', "right ring"),
+            (';', "right pinky"),
+            ('\'', "right pinky"),
+        ];
+        for (c, name) in home_row {
+            assert_eq!(finger(&qwerty, c), Some(name), "{}", c);
+        }
+        assert_eq!(finger(&qwerty, ' '), Some("thumbs"));
+        assert_eq!(finger(&qwerty, 'A'), Some("left pinky"));
+        assert_eq!(finger(&qwerty, '1'), Some("left pinky"));
+        assert_eq!(finger(&qwerty, '5'), Some("left index"));
+        assert_eq!(finger(&qwerty, '6'), Some("right index"));
+        assert_eq!(finger(&qwerty, '='), Some("right pinky"));
+        assert_eq!(finger(&qwerty, 'b'), Some("left index"));
+        assert_eq!(finger(&qwerty, 'é'), None);
+        // the finger goes with the place of the key, not the char
+        assert_eq!(finger(&dvorak(), 'o'), Some("left ring"));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::keyboard::Keyboard;
+use crate::storage;
+use crate::word_lists;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io;
+
+const PROGRESS_FILE: &str = "lessons";
+// what it takes to unlock the next lesson
+const TARGET_WPM: f64 = 25.;
+const TARGET_ACCURACY: f64 = 95.;
+// share of made up letter groups in the text, more when few real words can be typed yet
+const GROUP_SHARE: f64 = 0.4;
+const CAPITALIZED_SHARE: f64 = 0.3;
+
+pub struct Lesson {
+    pub name: &'static str,
+    // the keys the lesson adds to the ones of the lessons before it
+    pub keys: String,
+}
+
+// the lessons for a layout, rows of letters first and everything else after
+pub fn lessons(keyboard: &Keyboard) -> Vec<Lesson> {
+    // the pinky keys at the end of the rows wait for the symbols lesson
+    let row = |row: usize| -> String {
+        keyboard
+            .keys(row)
+            .iter()
+            .take(10)
+            .map(|&(unshifted, _)| unshifted)
+            .collect()
+    };
+    let all_keys = || {
+        (0..4).flat_map(|row| {
+            keyboard
+                .keys(row)
+                .iter()
+                .flat_map(|&(unshifted, shifted)| [unshifted, shifted])
+        })
+    };
+
+    let mut lessons = vec![
+        Lesson {
+            name: "home row",
+            keys: row(2),
+        },
+        Lesson {
+            name: "top row",
+            keys: row(1),
+        },
+        Lesson {
+            name: "bottom row",
+            keys: row(3),
+        },
+        Lesson {
+            name: "shift",
+            keys: all_keys().filter(|c| c.is_uppercase()).collect(),
+        },
+        Lesson {
+            name: "numbers",
+            keys: all_keys().filter(|c| c.is_ascii_digit()).collect(),
+        },
+    ];
+    let taught: String = lessons.iter().map(|lesson| lesson.keys.as_str()).collect();
+    lessons.push(Lesson {
+        name: "symbols",
+        keys: all_keys().filter(|&c| !taught.contains(c)).collect(),
+    });
+    // a layout file can leave a lesson without keys, like shift without uppercase letters
+    lessons.retain(|lesson| !lesson.keys.is_empty());
+    lessons
+}
+
+// the lesson the user is at, everything before it is passed
+pub fn current(lessons: &[Lesson]) -> io::Result<usize> {
+    let lesson = storage::read_data_file(PROGRESS_FILE)?
+        .trim()
+        .parse()
+        .unwrap_or(0);
+    Ok(lesson.min(lessons.len() - 1))
+}
+
+// words made of the keys unlocked so far, mixed with groups of letters that practice the new keys
+pub fn text(
+    lessons: &[Lesson],
+    lesson: usize,
+    amount_of_words: usize,
+    rng: &mut impl Rng,
+) -> String {
+    let unlocked: Vec<char> = lessons[..=lesson]
+        .iter()
+        .flat_map(|lesson| lesson.keys.chars())
+        .collect();
+    let new_keys: Vec<char> = lessons[lesson].keys.chars().collect();
+    let shift = unlocked.iter().any(|c| c.is_uppercase());
+    let words: Vec<&str> = word_lists::DEFAULT_ENGLISH
+        .iter()
+        .copied()
+        .filter(|word| word.chars().all(|c| unlocked.contains(&c)))
+        .collect();
+
+    let mut text = Vec::with_capacity(amount_of_words);
+    for _ in 0..amount_of_words {
+        let word = match words.choose(rng) {
+            Some(word) if !rng.gen_bool(GROUP_SHARE) => {
+                if shift && rng.gen_bool(CAPITALIZED_SHARE) {
+                    let mut chars = word.chars();
+                    chars.next().unwrap().to_uppercase().chain(chars).collect()
+                } else {
+                    word.to_string()
+                }
+            }
+            // half of every group is new keys
+            _ => (0..rng.gen_range(2..=5))
+                .map(|_| match rng.gen_bool(0.5) {
+                    true => *new_keys.choose(rng).unwrap(),
+                    false => *unlocked.choose(rng).unwrap(),
+                })
+                .collect(),
+        };
+        text.push(word);
+    }
+    text.join(" ")
+}
+
+// unlock the next lesson if the test was good enough, returns how the lesson went
+pub fn complete(lessons: &[Lesson], lesson: usize, wpm: f64, accuracy: f64) -> io::Result<String> {
+    let name = lessons[lesson].name;
+    if wpm < TARGET_WPM || accuracy < TARGET_ACCURACY {
+        return Ok(format!(
+            "{}, {} wpm and {}% accuracy to pass",
+            name, TARGET_WPM, TARGET_ACCURACY
+        ));
+    }
+    if lesson + 1 == lessons.len() {
+        return Ok(format!("{} passed, all lessons done", name));
+    }
+    // passing an older lesson again doesn't go back
+    if current(lessons)? <= lesson {
+        storage::write_data_file(PROGRESS_FILE, &format!("{}\n", lesson + 1))?;
+    }
+    Ok(format!(
+        "{} passed, {} unlocked",
+        name,
+        lessons[lesson + 1].name
+    ))
+}
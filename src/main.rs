@@ -1,62 +1,361 @@
 use crossterm::{
     cursor,
-    event::{poll, read, Event, KeyCode},
+    event::{
+        poll, read, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent,
+        KeyModifiers,
+    },
     execute, queue,
-    style::{Print, PrintStyledContent, Stylize},
+    style::{Color, Print, PrintStyledContent, StyledContent, Stylize},
     terminal,
 };
+use rand::rngs::StdRng;
 use rand::seq::IteratorRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
+use std::collections::BTreeMap;
 use std::io::{self, IsTerminal, Read, Write};
-use std::time::{Duration, SystemTime};
+use std::path::Path;
+use std::process;
+use std::time::Duration;
 
+use adaptive::Weaknesses;
+use book::Book;
+use drill::Deck;
+use filter::WordFilter;
+use ghost::Ghost;
+use lessons::Lesson;
+use options::{Command, Options};
+use pace::Pace;
+use results::{Results, View};
+use runs::Run;
+use typing_test::TypingTest;
+
+mod adaptive;
+mod book;
+mod cast;
+mod chart;
+mod drill;
+mod filter;
+mod ghost;
+mod history;
+mod keyboard;
+mod lessons;
+mod normalize;
+mod options;
+mod pace;
+mod replay;
+mod results;
+mod runs;
+mod stats;
+mod storage;
+mod typing_test;
 mod word_lists;
 
-fn main() -> io::Result<()> {
-    let (mut width, mut height) = terminal::size()?;
+const IDLE_HINT: &str = "start typing or paste a text, F2: live stats, F3: keyboard";
+const RESULTS_HINT: &str = "r: retry the same text, n: new text, v: next view, q: quit";
+const TEST_DURATION: Duration = Duration::from_secs(10);
+// live wpm only looks at the last few seconds so it reacts to slowing down
+const LIVE_WPM_WINDOW: Duration = Duration::from_secs(5);
+// rows the on-screen keyboard takes up, with the empty one above it
+const KEYBOARD_HEIGHT: u16 = 6;
+const TEXT_WORDS: usize = 50;
+// at most this many words of a drill text come from the deck, the rest is filler
+const DRILL_WORDS: usize = 20;
+// with fewer real words left by the filter the text gets made up words mixed in
+const MIN_FILTERED_WORDS: usize = 30;
+
+// how a single test came to an end
+enum TestEnd {
+    // time ran out, the text was done or the test failed
+    Over,
+    // esc before any of that
+    Aborted,
+    // tab and enter
+    Restart,
+    Quit,
+}
+
+// what to do after looking at the results
+enum Next {
+    Retry,
+    NewText,
+    Quit,
+}
+
+struct App {
+    options: Options,
+    width: u16,
+    height: u16,
+    text: String,
+    book: Option<Book>,
+    live_stats: bool,
+    show_keyboard: bool,
+    // the key pressed by mistake last, shown on the keyboard until the next key
+    wrong_key: Option<char>,
+    // what the current text is made to practice, if it was generated from the history
+    weaknesses: Option<Weaknesses>,
+    // how many words of the current text were due in the drill deck
+    drilling: Option<usize>,
+    // the lesson the current text is for
+    lesson: Option<usize>,
+    lessons: Vec<Lesson>,
+    // chars typed on the os layout and what they are on the emulated layout
+    remap: BTreeMap<char, char>,
+    // the run the tests race against, as long as the text stays the same
+    ghost: Option<Ghost>,
+    pace: Option<Pace>,
+    // what the current text was generated from, if it was
+    seed: Option<u64>,
+}
+
+fn main() {
+    // the message only, the debug output of an io::Error is for developers
+    if let Err(err) = try_main() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn try_main() -> io::Result<()> {
+    let options = Options::parse()?;
+    match &options.command {
+        Command::Test => {}
+        Command::Stats => return history::print_stats(&options.layout),
+        Command::Replay(path) => {
+            let run = Run::load(path)?;
+            terminal::enable_raw_mode()?;
+            execute!(io::stdout(), terminal::EnterAlternateScreen)?;
+            let played = replay::play(run, options.speed);
+            terminal::disable_raw_mode()?;
+            execute!(io::stdout(), terminal::LeaveAlternateScreen)?;
+            return played;
+        }
+        Command::Cast(path) => {
+            // named after the run and put where the user is, to be shared from there
+            let cast_path = Path::new(path.file_stem().unwrap_or_default()).with_extension("cast");
+            cast::export(Run::load(path)?, &cast_path)?;
+            println!("saved {}", cast_path.display());
+            return Ok(());
+        }
+    }
+    let (width, height) = terminal::size()?;
+
+    let mut book = options
+        .book
+        .as_deref()
+        .map(|path| Book::open(path, &options.normalization))
+        .transpose()?;
+
+    let pace = options
+        .pace
+        .as_ref()
+        .map(|target| Pace::new(target, &options.rules.category()))
+        .transpose()?;
+    let ghost_run = match options.ghost.as_deref() {
+        Some("best") => match runs::best(&options.rules.category())? {
+            Some(run) => Some(run),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "no saved runs in {} mode to race yet",
+                        options.rules.category()
+                    ),
+                ))
+            }
+        },
+        Some(path) => Some(Run::load(Path::new(path))?),
+        None => None,
+    };
+    // a race is only fair with the same rules on both sides
+    if let Some(run) = &ghost_run {
+        if run.category != options.rules.category() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the run was typed in {} mode, start with the same options to race it",
+                    run.category
+                ),
+            ));
+        }
+    }
+    let ghost = ghost_run.map(Ghost::new);
 
-    // get text to write from stdin or random from 10_000 most common english words
+    // get text to write from the ghost, a book, stdin or random from 10_000 most common english words
     let mut text: String = String::new();
-    if !io::stdin().is_terminal() {
+    if let Some(ghost) = &ghost {
+        text = ghost.text().to_string();
+    } else if let Some(book) = &mut book {
+        text = book.next_chunk();
+    } else if !io::stdin().is_terminal() {
         // so, if stdin is from a program piped into this program
         io::stdin().read_to_string(&mut text)?;
+        text = normalize::normalize(&text, &options.normalization);
     }
     text = text.trim().to_string();
-    if text.is_empty() {
-        text = get_text(50)?;
+
+    let mut app = App {
+        live_stats: options.live_stats,
+        show_keyboard: options.show_keyboard,
+        wrong_key: None,
+        width,
+        height,
+        text,
+        book,
+        weaknesses: None,
+        drilling: None,
+        lesson: None,
+        lessons: lessons::lessons(&options.layout),
+        remap: options.layout.remap_from(&keyboard::qwerty()),
+        ghost,
+        pace,
+        seed: None,
+        options,
+    };
+    if app.text.is_empty() {
+        app.text = app.generate_text()?;
     }
-    let duration: Duration = Duration::from_secs(10);
-    let mut lines = split_into_lines(&text, width as usize / 2);
 
     // create raw buffer
     terminal::enable_raw_mode()?;
-    execute!(io::stdout(), terminal::EnterAlternateScreen)?;
+    execute!(
+        io::stdout(),
+        terminal::EnterAlternateScreen,
+        EnableBracketedPaste
+    )?;
 
-    init_lines(&lines, width, height)?;
-    io::stdout().flush()?;
+    let last_results = app.run();
 
-    let mut cursor_index = 0;
-    let mut cursor_line_index = 0;
+    // disable raw buffer
+    terminal::disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        DisableBracketedPaste,
+        terminal::LeaveAlternateScreen
+    )?;
+
+    // the last results stay visible after leaving
+    if let Some(results) = last_results? {
+        for line in results
+            .lines()
+            .into_iter()
+            .chain(results.view_lines(View::Chart, &app.options.layout))
+        {
+            println!("{}", line);
+        }
+    }
+
+    Ok(())
+}
 
-    let start_time = SystemTime::now();
+impl App {
+    // tests and their results one after the other, until the user quits
+    fn run(&mut self) -> io::Result<Option<Results>> {
+        let mut last_results = None;
+
+        loop {
+            let mut test = TypingTest::new(&self.text, self.options.rules);
+            let mut pastes_rejected = 0;
+            let aborted = match self.run_test(&mut test, &mut pastes_rejected)? {
+                TestEnd::Over => false,
+                TestEnd::Aborted => true,
+                TestEnd::Restart => continue,
+                TestEnd::Quit => return Ok(last_results),
+            };
+            test.stop();
+
+            let mut results = Results::new(&test, &self.options.rules, pastes_rejected, aborted);
+            results.save()?;
+            results.replay = Some(Run::new(&test, &self.text, &results, self.seed).save()?);
+            if let Some(ghost) = &mut self.ghost {
+                results.race = Some(ghost.margin(&test));
+            }
+            if let Some(pace) = &self.pace {
+                results.pace = Some(pace.margin(&test));
+            }
+            let mut deck = Deck::load()?;
+            deck.review(&test, drill::min_wpm(&results.category)?);
+            deck.save()?;
+            // a word or two typed fast enough doesn't pass a lesson
+            if let (Some(lesson), false) = (self.lesson, aborted) {
+                results.lesson = Some(lessons::complete(
+                    &self.lessons,
+                    lesson,
+                    results.wpm,
+                    results.accuracy,
+                )?);
+            }
+            if let Some(book) = &mut self.book {
+                // next time resume at the start of the word that was interrupted
+                book.advance(test.completed_chars())?;
+                results.book = Some((book.progress(), book.offset(), book.len()));
+            }
+
+            let next = self.show_results(&results)?;
+            last_results = Some(results);
+            match next {
+                Next::Retry => {}
+                Next::NewText => self.text = self.new_text()?,
+                Next::Quit => return Ok(last_results),
+            }
+        }
+    }
+
+    fn run_test(
+        &mut self,
+        test: &mut TypingTest,
+        pastes_rejected: &mut usize,
+    ) -> io::Result<TestEnd> {
+        let duration = TEST_DURATION;
+        // tab only restarts together with the enter right after it
+        let mut tab_pressed = false;
+        self.wrong_key = None;
+        if let Some(ghost) = &mut self.ghost {
+            ghost.reset();
+        }
+
+        queue!(io::stdout(), terminal::Clear(terminal::ClearType::All))?;
+        self.draw_test(test)?;
+        draw_message(self.idle_hint().dark_grey(), self.width, self.height)?;
+        io::stdout().flush()?;
+        let mut drawn_carets = self.carets(test);
+
+        loop {
+            // the timer starts with the first typed key, until then the test is idle
+            let remaining_time = match test.elapsed() {
+                Some(elapsed) => match duration.checked_sub(elapsed) {
+                    Some(remaining_time) => remaining_time,
+                    None => return Ok(TestEnd::Over),
+                },
+                None => duration,
+            };
+            draw_timer(&mut io::stdout(), &remaining_time, &duration, self.width)?;
+            if let (Some(ghost), Some(elapsed)) = (&mut self.ghost, test.elapsed()) {
+                ghost.catch_up(elapsed);
+            }
+            // the ghost and the pace move on their own
+            let carets = self.carets(test);
+            if carets != drawn_carets {
+                self.draw_test(test)?;
+                io::stdout().flush()?;
+                drawn_carets = carets;
+            }
+            if self.live_stats {
+                draw_live_stats(test, self.width)?;
+            }
+            if !poll(Duration::from_millis(100))? {
+                continue;
+            }
 
-    while let Some(remaining_time) = start_time
-        .elapsed()
-        .ok()
-        .and_then(|elapsed| duration.checked_sub(elapsed))
-    {
-        draw_timer(&remaining_time, &duration, width)?;
-        if poll(Duration::from_millis(100))? {
             match read()? {
                 Event::Resize(w, h) => {
-                    width = w;
-                    height = h;
-                    cursor_index = 0;
-                    cursor_line_index = 0;
+                    self.width = w;
+                    self.height = h;
 
-                    lines = split_into_lines(&text, (width / 2) as usize);
-
-                    init_lines(&lines, width, height)?;
+                    queue!(io::stdout(), terminal::Clear(terminal::ClearType::All))?;
+                    self.draw_test(test)?;
+                    if test.elapsed().is_none() {
+                        draw_message(self.idle_hint().dark_grey(), self.width, self.height)?;
+                    }
                     io::stdout().flush()?;
                 }
                 Event::FocusGained => {
@@ -67,160 +366,255 @@ fn main() -> io::Result<()> {
                 }
                 // Mouse capture disabled by default
                 // Event::Mouse(_) => {}
-                Event::Paste(_) => {}
+                Event::Paste(pasted) => {
+                    if test.elapsed().is_some() {
+                        // pasting into a running test would be cheating
+                        *pastes_rejected += 1;
+                        draw_message("paste rejected".to_string().red(), self.width, self.height)?;
+                        io::stdout().flush()?;
+                        continue;
+                    }
 
-                Event::Key(keyevent) => match keyevent.code {
-                    KeyCode::Char(key) => {
-                        if key == lines[cursor_line_index].chars().nth(cursor_index).unwrap() {
-                            queue!(io::stdout(), PrintStyledContent(key.green()))?;
-                        } else if key == ' ' {
-                            queue!(io::stdout(), PrintStyledContent("█".red()))?;
-                        } else {
-                            queue!(io::stdout(), PrintStyledContent(key.red()))?;
+                    // while idle a paste becomes the text to type
+                    if self.use_pasted(&pasted) {
+                        *test = TypingTest::new(&self.text, self.options.rules);
+                        self.draw_test(test)?;
+                        // the hint no longer mentions the lesson, ghost or drill the paste replaced
+                        draw_message(self.idle_hint().dark_grey(), self.width, self.height)?;
+                        io::stdout().flush()?;
+                    }
+                }
+
+                Event::Key(keyevent) => {
+                    let restart = tab_pressed && keyevent.code == KeyCode::Enter;
+                    tab_pressed = keyevent.code == KeyCode::Tab;
+                    match keyevent.code {
+                        _ if restart => return Ok(TestEnd::Restart),
+                        _ if is_delete_word(&keyevent) => {
+                            test.delete_word();
+                            self.draw_test(test)?;
+                            io::stdout().flush()?;
                         }
+                        // other shortcuts aren't part of the text
+                        KeyCode::Char(_) if keyevent.modifiers.contains(KeyModifiers::CONTROL) => {}
+                        KeyCode::Char(key) => {
+                            if test.elapsed().is_none() {
+                                draw_message("".to_string().reset(), self.width, self.height)?;
+                            }
+                            let key = *self.remap.get(&key).unwrap_or(&key);
+                            let errors = test.incorrect_keys;
+                            test.type_key(key);
+                            self.wrong_key = (test.incorrect_keys > errors).then_some(key);
+                            self.draw_test(test)?;
+                            io::stdout().flush()?;
 
-                        if cursor_index == lines[cursor_line_index].len() - 1 {
-                            cursor_line_index += 1;
-                            cursor_index = 0;
-                            if cursor_line_index == lines.len() {
-                                break;
+                            if test.is_finished() || test.failure.is_some() {
+                                return Ok(TestEnd::Over);
                             }
-                            queue!(
-                                io::stdout(),
-                                cursor::MoveTo(
-                                    (width - lines[cursor_line_index].len() as u16) / 2,
-                                    (height - lines.len() as u16) / 2 + cursor_line_index as u16,
-                                )
-                            )?;
-                        } else {
-                            cursor_index += 1;
                         }
-
-                        io::stdout().flush()?;
-                    }
-                    KeyCode::Backspace => {
-                        if cursor_index == 0 && cursor_line_index == 0 {
-                            continue;
-                        } else if cursor_index == 0 {
-                            cursor_line_index -= 1;
-                            cursor_index = lines[cursor_line_index].len() - 1;
-
-                            queue!(
-                                io::stdout(),
-                                cursor::MoveTo(
-                                    (width - lines[cursor_line_index].len() as u16) / 2
-                                        + lines[cursor_line_index].len() as u16
-                                        - 1,
-                                    (height - lines.len() as u16) / 2 + cursor_line_index as u16,
-                                )
-                            )?;
-                            queue!(
-                                io::stdout(),
-                                PrintStyledContent(
-                                    lines[cursor_line_index].chars().last().unwrap().blue(),
-                                )
-                            )?;
-                            queue!(io::stdout(), cursor::MoveLeft(1))?;
-                        } else {
-                            cursor_index -= 1;
-                            queue!(io::stdout(), cursor::MoveLeft(1))?;
-                            queue!(
-                                io::stdout(),
-                                PrintStyledContent(
-                                    lines[cursor_line_index]
-                                        .chars()
-                                        .nth(cursor_index)
-                                        .unwrap()
-                                        .blue(),
-                                )
-                            )?;
-                            queue!(io::stdout(), cursor::MoveLeft(1))?;
+                        KeyCode::Backspace => {
+                            test.backspace();
+                            self.wrong_key = None;
+                            self.draw_test(test)?;
+                            io::stdout().flush()?;
                         }
-
-                        io::stdout().flush()?;
+                        KeyCode::F(2) => {
+                            self.live_stats = !self.live_stats;
+                            if !self.live_stats {
+                                clear_live_stats(self.width)?;
+                            }
+                        }
+                        KeyCode::F(3) => {
+                            self.show_keyboard = !self.show_keyboard;
+                            self.draw_test(test)?;
+                            io::stdout().flush()?;
+                        }
+                        // ending a test that never started leaves right away
+                        KeyCode::Esc if test.elapsed().is_none() => return Ok(TestEnd::Quit),
+                        KeyCode::Esc => return Ok(TestEnd::Aborted),
+                        _ => {}
                     }
-                    KeyCode::Esc => break,
-                    _ => {}
-                },
-                _ => break,
+                }
+                _ => return Ok(TestEnd::Over),
             }
         }
     }
-    // disable raw buffer
-    terminal::disable_raw_mode()?;
-    execute!(io::stdout(), terminal::LeaveAlternateScreen)?;
-
-    // millis instead of second for higher accuracy
-    let time_typed = start_time.elapsed().unwrap().as_millis();
-
-    let mut words_typed = 0;
-    let mut chars_typed = 0;
-    for (index, line) in lines.iter().enumerate() {
-        if index >= cursor_line_index {
-            words_typed += line[0..cursor_index].split_whitespace().count();
-            chars_typed += cursor_index;
-            break;
-        }
-        words_typed += line.split_whitespace().count();
-        chars_typed += line.len();
+
+    // where the ghost and the pace are in the text
+    fn carets(&self, test: &TypingTest) -> Vec<((usize, usize), Color)> {
+        let ghost = self
+            .ghost
+            .iter()
+            .map(|ghost| (ghost.position(), Color::DarkMagenta));
+        let pace = self
+            .pace
+            .iter()
+            .map(|pace| (pace.position(test), Color::DarkCyan));
+        ghost.chain(pace).collect()
     }
-    let pure_wpm = words_typed as f64 * (60000. / time_typed as f64);
-    let raw_wpm = chars_typed as f64 / 5. * (60000. / time_typed as f64);
 
-    println!(" time typed: {}", time_typed / 1000);
-    println!("words typed: {}", words_typed);
-    println!("chars typed: {}", chars_typed);
-    println!("   pure wpm: {:.2}", pure_wpm);
-    println!("    raw wpm: {:.2}", raw_wpm);
+    // the text, with the keyboard below it when it is shown
+    fn draw_test(&self, test: &TypingTest) -> io::Result<()> {
+        let carets = self.carets(test);
+        if !self.show_keyboard {
+            return test.draw(&mut io::stdout(), self.width, self.height, &carets);
+        }
 
-    Ok(())
-}
+        let top = self.height.saturating_sub(KEYBOARD_HEIGHT);
+        test.draw(&mut io::stdout(), self.width, top, &carets)?;
+        let lines = self
+            .options
+            .layout
+            .diagram(test.next_char(), self.wrong_key);
+        let column = self
+            .width
+            .saturating_sub(self.options.layout.width() as u16)
+            / 2;
+        queue!(io::stdout(), cursor::SavePosition)?;
+        for (i, line) in lines.iter().enumerate() {
+            let row = top + i as u16;
+            queue!(
+                io::stdout(),
+                cursor::MoveTo(0, row),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                cursor::MoveTo(column, row),
+                Print(line)
+            )?;
+        }
+        queue!(io::stdout(), cursor::RestorePosition)
+    }
+
+    fn show_results(&mut self, results: &Results) -> io::Result<Next> {
+        let mut view = View::Chart;
+        loop {
+            results.draw(self.width, self.height, view, &self.options.layout)?;
+            draw_message(
+                RESULTS_HINT.to_string().dark_grey(),
+                self.width,
+                self.height,
+            )?;
+            io::stdout().flush()?;
 
-fn split_into_lines(text: &str, length_of_line: usize) -> Vec<String> {
-    let words = text.split_whitespace();
+            match read()? {
+                Event::Resize(w, h) => {
+                    self.width = w;
+                    self.height = h;
+                }
+                Event::Paste(pasted) if self.use_pasted(&pasted) => return Ok(Next::Retry),
+                Event::Key(keyevent) => match keyevent.code {
+                    KeyCode::Char('r') | KeyCode::Tab => return Ok(Next::Retry),
+                    KeyCode::Char('n') | KeyCode::Enter => return Ok(Next::NewText),
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(Next::Quit),
+                    KeyCode::Char('v') => view = view.next(),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
 
-    let mut lines: Vec<String> = Vec::new();
-    let mut line: String = String::new();
+    // make pasted text the text to type, returns whether there was anything to type in it
+    fn use_pasted(&mut self, pasted: &str) -> bool {
+        let pasted = normalize::normalize(pasted, &self.options.normalization);
+        if pasted.trim().is_empty() {
+            return false;
+        }
+        self.text = pasted.trim().to_string();
+        self.book = None;
+        self.weaknesses = None;
+        self.drilling = None;
+        self.lesson = None;
+        self.ghost = None;
+        self.seed = None;
+        true
+    }
 
-    for word in words {
-        if line.len() + word.len() > length_of_line {
-            lines.push(line);
-            line = String::new();
+    // the next part of the book, or random words
+    fn new_text(&mut self) -> io::Result<String> {
+        // the ghost only races on its own text
+        self.ghost = None;
+        self.seed = None;
+        match &mut self.book {
+            Some(book) => Ok(book.next_chunk()),
+            None => self.generate_text(),
         }
-        line.push_str(word);
-        line.push(' ');
     }
 
-    // pop of space at the end
-    line.pop();
-    lines.push(line);
+    // random words, biased towards the weaknesses in the history when practicing adaptively
+    fn generate_text(&mut self) -> io::Result<String> {
+        // the seed is saved with the run, so the text can be made again
+        let seed = thread_rng().gen();
+        self.seed = Some(seed);
+        let mut rng = StdRng::seed_from_u64(seed);
 
-    lines
-}
+        if self.options.lessons {
+            let lesson = lessons::current(&self.lessons)?;
+            self.lesson = Some(lesson);
+            return Ok(lessons::text(&self.lessons, lesson, TEXT_WORDS, &mut rng));
+        }
 
-fn init_lines(lines: &[String], width: u16, height: u16) -> io::Result<()> {
-    queue!(io::stdout(), terminal::Clear(terminal::ClearType::All))?;
-    let start_height = (height - lines.len() as u16) / 2;
-    for (i, line) in lines.iter().enumerate() {
-        let start_width = (width - line.len() as u16) / 2;
-        queue!(
-            io::stdout(),
-            cursor::MoveTo(start_width, start_height + i as u16)
+        self.weaknesses = match self.options.adaptive {
+            Some(strength) => Some(Weaknesses::from_history(&history::load()?, strength))
+                .filter(|weaknesses| !weaknesses.is_empty()),
+            None => None,
+        };
+        if !self.options.drill {
+            return get_text(
+                TEXT_WORDS,
+                &self.options.filter,
+                self.weaknesses.as_ref(),
+                &mut rng,
+            );
+        }
+
+        let mut due = Deck::load()?.due();
+        due.truncate(DRILL_WORDS);
+        self.drilling = Some(due.len());
+        let filler = get_text(
+            TEXT_WORDS - due.len(),
+            &self.options.filter,
+            self.weaknesses.as_ref(),
+            &mut rng,
         )?;
-        queue!(io::stdout(), PrintStyledContent(line.clone().blue()))?;
+        Ok(drill::mix_in(&due, &filler, &mut rng))
     }
 
-    queue!(
-        io::stdout(),
-        cursor::MoveTo((width - lines[0].len() as u16) / 2, start_height)
-    )?;
-    Ok(())
+    fn idle_hint(&self) -> String {
+        let mut hint = String::new();
+        if let Some(ghost) = &self.ghost {
+            hint.push_str(&format!("racing a ghost at {:.0} wpm, ", ghost.wpm()));
+        }
+        if let Some(pace) = &self.pace {
+            hint.push_str(&format!("pace at {:.0} wpm, ", pace.wpm));
+        }
+        if let Some(lesson) = self.lesson {
+            hint.push_str(&format!(
+                "lesson {}/{} {}: {}, ",
+                lesson + 1,
+                self.lessons.len(),
+                self.lessons[lesson].name,
+                self.lessons[lesson].keys
+            ));
+        }
+        if let Some(due) = self.drilling {
+            hint.push_str(&format!("drilling {} due words, ", due));
+        }
+        if let Some(weaknesses) = &self.weaknesses {
+            hint.push_str(&format!("practicing {}, ", weaknesses.describe()));
+        }
+        hint + IDLE_HINT
+    }
 }
 
-fn draw_timer(remaining_time: &Duration, duration: &Duration, width: u16) -> io::Result<()> {
+fn draw_timer(
+    out: &mut impl Write,
+    remaining_time: &Duration,
+    duration: &Duration,
+    width: u16,
+) -> io::Result<()> {
     // draw number
     execute!(
-        io::stdout(),
+        out,
         cursor::SavePosition,
         cursor::MoveTo(1, 1),
         // print extra whitespaces so there aren't any trailing digits
@@ -230,7 +624,7 @@ fn draw_timer(remaining_time: &Duration, duration: &Duration, width: u16) -> io:
 
     // draw bar
     execute!(
-        io::stdout(),
+        out,
         cursor::SavePosition,
         cursor::MoveTo(0, 0),
         Print(" ".repeat(width as usize)),
@@ -249,11 +643,108 @@ fn draw_timer(remaining_time: &Duration, duration: &Duration, width: u16) -> io:
     Ok(())
 }
 
-fn get_text(amount_of_words: usize) -> io::Result<String> {
+// current speed and accuracy in the top right corner, out of the way of the text
+fn draw_live_stats(test: &TypingTest, width: u16) -> io::Result<()> {
+    let stats = format!(
+        "{:>4.0} wpm {:>4.0}% acc",
+        test.rolling_wpm(LIVE_WPM_WINDOW),
+        test.accuracy()
+    );
+    execute!(
+        io::stdout(),
+        cursor::SavePosition,
+        cursor::MoveTo(width.saturating_sub(stats.len() as u16 + 1), 1),
+        PrintStyledContent(stats.yellow()),
+        cursor::RestorePosition
+    )
+}
+
+fn clear_live_stats(width: u16) -> io::Result<()> {
+    execute!(
+        io::stdout(),
+        cursor::SavePosition,
+        cursor::MoveTo(width / 2, 1),
+        terminal::Clear(terminal::ClearType::UntilNewLine),
+        cursor::RestorePosition
+    )
+}
+
+// terminals send ctrl+backspace and alt+backspace in different ways
+fn is_delete_word(keyevent: &KeyEvent) -> bool {
+    match keyevent.code {
+        KeyCode::Backspace => keyevent
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT),
+        KeyCode::Char('h' | 'w') => keyevent.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+// one line message at the bottom of the screen, replacing the previous one,
+// cut to the width so it doesn't wrap onto a new line and scroll the screen
+fn draw_message(message: StyledContent<String>, width: u16, height: u16) -> io::Result<()> {
+    let text: String = message
+        .content()
+        .chars()
+        .take(width.saturating_sub(2) as usize)
+        .collect();
+    let message = StyledContent::new(*message.style(), text);
+    queue!(
+        io::stdout(),
+        cursor::SavePosition,
+        cursor::MoveTo(1, height - 1),
+        terminal::Clear(terminal::ClearType::CurrentLine),
+        PrintStyledContent(message),
+        cursor::RestorePosition
+    )
+}
+
+fn get_text(
+    amount_of_words: usize,
+    filter: &WordFilter,
+    weaknesses: Option<&Weaknesses>,
+    rng: &mut impl Rng,
+) -> io::Result<String> {
     let mut text = String::new();
-    let mut rng = thread_rng();
+    let words: Vec<&str> = word_lists::DEFAULT_ENGLISH
+        .iter()
+        .copied()
+        .filter(|word| filter.matches(word))
+        .collect();
+    let targeted: Vec<&str> = match weaknesses {
+        Some(weaknesses) => words
+            .iter()
+            .copied()
+            .filter(|word| weaknesses.targets(word))
+            .collect(),
+        None => Vec::new(),
+    };
     for _ in 0..amount_of_words {
-        text.push_str(word_lists::DEFAULT_ENGLISH.iter().choose(&mut rng).unwrap());
+        let word = match weaknesses {
+            Some(weaknesses) if !targeted.is_empty() && rng.gen_bool(weaknesses.strength) => {
+                targeted.iter().choose(rng)
+            }
+            _ if words.len() < MIN_FILTERED_WORDS
+                && !rng.gen_ratio(words.len() as u32, MIN_FILTERED_WORDS as u32) =>
+            {
+                None
+            }
+            _ => words.iter().choose(rng),
+        };
+        // a real word after all when no made up one matches the filter
+        let word = match word {
+            Some(word) => word.to_string(),
+            None => filter
+                .synthetic_word(rng)
+                .or_else(|| words.iter().choose(rng).map(|word| word.to_string()))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "no words match the filter, not even made up ones",
+                    )
+                })?,
+        };
+        text.push_str(&word);
         text.push(' ');
     }
     Ok(text)
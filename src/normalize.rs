@@ -0,0 +1,144 @@
+// turns text into something that can be typed on a standard keyboard
+
+pub struct Normalization {
+    pub enabled: bool,
+    // also replace accented letters with their plain counterpart
+    pub strip_accents: bool,
+}
+
+pub fn normalize(text: &str, normalization: &Normalization) -> String {
+    if !normalization.enabled {
+        return text.to_string();
+    }
+
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '‘' | '’' | '‚' | '‛' | '′' | '´' => normalized.push('\''),
+            '“' | '”' | '„' | '‟' | '″' | '«' | '»' => normalized.push('"'),
+            '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => normalized.push('-'),
+            '…' => normalized.push_str("..."),
+            'ﬀ' => normalized.push_str("ff"),
+            'ﬁ' => normalized.push_str("fi"),
+            'ﬂ' => normalized.push_str("fl"),
+            'ﬃ' => normalized.push_str("ffi"),
+            'ﬄ' => normalized.push_str("ffl"),
+            'ﬅ' | 'ﬆ' => normalized.push_str("st"),
+            '\u{a0}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' => {
+                normalized.push(' ')
+            }
+            // soft hyphen and zero width characters
+            '\u{ad}' | '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}' => {}
+            // keep line breaks and tabs, split_whitespace takes care of them
+            c if c.is_control() && !c.is_whitespace() => {}
+            c if normalization.strip_accents => match strip_accent(c) {
+                Some(plain) => normalized.push_str(plain),
+                None => normalized.push(c),
+            },
+            c => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+fn strip_accent(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ď' | 'Đ' => "D",
+        'ď' | 'đ' => "d",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'ß' => "ss",
+        'Þ' => "Th",
+        'þ' => "th",
+        'Ð' => "D",
+        'ð' => "d",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: Normalization = Normalization {
+        enabled: true,
+        strip_accents: false,
+    };
+
+    #[test]
+    fn typographic_chars_become_ascii() {
+        assert_eq!(
+            normalize("“Don’t” – wait…", &DEFAULT),
+            "\"Don't\" - wait..."
+        );
+        assert_eq!(normalize("ﬁne ﬂow", &DEFAULT), "fine flow");
+        assert_eq!(normalize("a\u{a0}b\u{2009}c", &DEFAULT), "a b c");
+        assert_eq!(
+            normalize("soft\u{ad}hy\u{200b}phen\u{7}", &DEFAULT),
+            "softhyphen"
+        );
+        // split_whitespace still needs these
+        assert_eq!(normalize("a\nb\tc", &DEFAULT), "a\nb\tc");
+    }
+
+    #[test]
+    fn accents_only_go_when_asked() {
+        assert_eq!(normalize("café Œuvre", &DEFAULT), "café Œuvre");
+        let strip = Normalization {
+            strip_accents: true,
+            ..DEFAULT
+        };
+        assert_eq!(
+            normalize("café Œuvre straße", &strip),
+            "cafe OEuvre strasse"
+        );
+    }
+
+    #[test]
+    fn disabled_keeps_everything() {
+        let disabled = Normalization {
+            enabled: false,
+            strip_accents: true,
+        };
+        assert_eq!(normalize("“é”…", &disabled), "“é”…");
+    }
+}
@@ -0,0 +1,203 @@
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::filter::WordFilter;
+use crate::keyboard::{self, Keyboard};
+use crate::normalize::Normalization;
+use crate::pace::PaceTarget;
+use crate::replay::SPEEDS;
+use crate::typing_test::{Backspace, Input, Rules, StopOnError};
+use regex::Regex;
+
+pub enum Command {
+    // the typing test itself
+    Test,
+    // summary of the history
+    Stats,
+    // play a saved run back
+    Replay(PathBuf),
+    // turn a saved run into an asciinema recording
+    Cast(PathBuf),
+}
+
+pub struct Options {
+    pub command: Command,
+    // file to type through in chunks across sessions
+    pub book: Option<PathBuf>,
+    // applied to piped text and books before they are split into lines
+    pub normalization: Normalization,
+    pub rules: Rules,
+    // show wpm and accuracy while typing, can be toggled with F2
+    pub live_stats: bool,
+    // keyboard below the text with the next key lit up, can be toggled with F3
+    pub show_keyboard: bool,
+    // share of generated words that practice the weakest keys in the history
+    pub adaptive: Option<f64>,
+    // mix the due words of the drill deck into generated text
+    pub drill: bool,
+    // generate text for the next touch typing lesson
+    pub lessons: bool,
+    // which words generated text is made of
+    pub filter: WordFilter,
+    // layout to emulate on top of a qwerty os layout, also the one the stats are drawn on
+    pub layout: Keyboard,
+    // saved run to race against, or best for the fastest one
+    pub ghost: Option<String>,
+    // speed of the pace caret
+    pub pace: Option<PaceTarget>,
+    // how fast replays play at first
+    pub speed: f64,
+}
+
+impl Options {
+    pub fn parse() -> io::Result<Options> {
+        let mut options = Options {
+            command: Command::Test,
+            book: None,
+            normalization: Normalization {
+                enabled: true,
+                strip_accents: false,
+            },
+            rules: Rules::default(),
+            live_stats: false,
+            show_keyboard: false,
+            adaptive: None,
+            drill: false,
+            lessons: false,
+            filter: WordFilter::default(),
+            layout: keyboard::qwerty(),
+            ghost: None,
+            pace: None,
+            speed: 1.,
+        };
+
+        let mut args = env::args().skip(1).peekable();
+        if args.next_if(|arg| arg == "stats").is_some() {
+            options.command = Command::Stats;
+        } else if args.next_if(|arg| arg == "replay").is_some() {
+            options.command = Command::Replay(PathBuf::from(value("replay", args.next())?));
+        } else if args.next_if(|arg| arg == "cast").is_some() {
+            options.command = Command::Cast(PathBuf::from(value("cast", args.next())?));
+        }
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-b" | "--book" => options.book = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--no-normalize" => options.normalization.enabled = false,
+                "--strip-accents" => options.normalization.strip_accents = true,
+                "--adaptive" => {
+                    let strength: f64 = parse(&arg, args.next())?;
+                    if !(0. ..=1.).contains(&strength) {
+                        return Err(invalid(format!("{} has to be between 0 and 1", arg)));
+                    }
+                    options.adaptive = Some(strength);
+                }
+                "--drill" => options.drill = true,
+                "--lessons" => options.lessons = true,
+                "--live-stats" => options.live_stats = true,
+                "--keyboard" => options.show_keyboard = true,
+                "--word-input" => options.rules.input = Input::Words,
+                "--strict" => options.rules.stop_on_error = StopOnError::Letter,
+                "--stop-on-word" => options.rules.stop_on_error = StopOnError::Word,
+                "--no-backspace" => options.rules.backspace = Backspace::Disabled,
+                "--confidence" => options.rules.backspace = Backspace::Confidence,
+                "--sudden-death" => options.rules.sudden_death = true,
+                "--min-accuracy" => {
+                    let min_accuracy: f64 = parse(&arg, args.next())?;
+                    if !(0. ..=100.).contains(&min_accuracy) {
+                        return Err(invalid(format!("{} has to be a percentage", arg)));
+                    }
+                    options.rules.min_accuracy = Some(min_accuracy);
+                }
+                "--ghost" => options.ghost = Some(value(&arg, args.next())?),
+                "--pace" => {
+                    options.pace = Some(match value(&arg, args.next())?.as_str() {
+                        "average" => PaceTarget::Average,
+                        "best" => PaceTarget::Best,
+                        wpm => match wpm.parse() {
+                            Ok(wpm) if wpm > 0. => PaceTarget::Wpm(wpm),
+                            _ => {
+                                return Err(invalid(format!(
+                                    "{} takes a wpm, average or best, not {}",
+                                    arg, wpm
+                                )))
+                            }
+                        },
+                    })
+                }
+                "--speed" => {
+                    let speed: f64 = parse(&arg, args.next())?;
+                    let (slowest, fastest) = (SPEEDS[0], SPEEDS[SPEEDS.len() - 1]);
+                    if !(slowest..=fastest).contains(&speed) {
+                        return Err(invalid(format!(
+                            "{} has to be between {} and {}",
+                            arg, slowest, fastest
+                        )));
+                    }
+                    options.speed = speed;
+                }
+                "--layout" => options.layout = keyboard::layout(&value(&arg, args.next())?)?,
+                "--only-letters" => {
+                    let letters: Vec<char> = value(&arg, args.next())?.chars().collect();
+                    if letters.is_empty() {
+                        return Err(invalid(format!("{} needs at least one letter", arg)));
+                    }
+                    // words are split at whitespace, it can't be in one
+                    if letters.iter().any(|c| c.is_whitespace()) {
+                        return Err(invalid(format!("{} can't contain whitespace", arg)));
+                    }
+                    options.filter.letters = Some(letters);
+                }
+                "--min-length" => options.filter.min_length = Some(parse(&arg, args.next())?),
+                "--max-length" => options.filter.max_length = Some(parse(&arg, args.next())?),
+                "--include" => options.filter.include = Some(regex(&arg, args.next())?),
+                "--exclude" => options.filter.exclude = Some(regex(&arg, args.next())?),
+                _ => return Err(invalid(format!("unknown argument: {}", arg))),
+            }
+        }
+
+        // a word with an error in it could never be left
+        if options.rules.stop_on_error == StopOnError::Word
+            && options.rules.backspace == Backspace::Disabled
+        {
+            return Err(invalid(
+                "--stop-on-word can't be combined with --no-backspace".to_string(),
+            ));
+        }
+        if options.ghost.is_some() && options.book.is_some() {
+            return Err(invalid(
+                "--ghost races on its own text, it can't be combined with --book".to_string(),
+            ));
+        }
+        if let (Some(min), Some(max)) = (options.filter.min_length, options.filter.max_length) {
+            if min > max {
+                return Err(invalid(
+                    "--min-length can't be more than --max-length".to_string(),
+                ));
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn value(arg: &str, value: Option<String>) -> io::Result<String> {
+    value.ok_or_else(|| invalid(format!("missing value for {}", arg)))
+}
+
+fn parse<T: FromStr>(arg: &str, value: Option<String>) -> io::Result<T> {
+    let value = self::value(arg, value)?;
+    value
+        .parse()
+        .map_err(|_| invalid(format!("invalid value for {}: {}", arg, value)))
+}
+
+fn regex(arg: &str, value: Option<String>) -> io::Result<Regex> {
+    let value = self::value(arg, value)?;
+    Regex::new(&value).map_err(|err| invalid(format!("invalid regex for {}: {}", arg, err)))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
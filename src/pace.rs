@@ -0,0 +1,63 @@
+use crate::ghost;
+use crate::history;
+use crate::typing_test::TypingTest;
+use std::io;
+use std::time::Duration;
+
+// what speed the pace caret goes at
+pub enum PaceTarget {
+    Wpm(f64),
+    // of the tests in the same mode in the history
+    Average,
+    Best,
+}
+
+// a caret moving through the text at the same speed all along
+pub struct Pace {
+    // in chars / 5 per minute like the raw wpm, not in whole words
+    pub wpm: f64,
+}
+
+impl Pace {
+    // look up the speed in the history of the mode if it has to
+    pub fn new(target: &PaceTarget, category: &str) -> io::Result<Pace> {
+        let wpms: Vec<f64> = match target {
+            PaceTarget::Wpm(wpm) => return Ok(Pace { wpm: *wpm }),
+            PaceTarget::Average | PaceTarget::Best => history::load()?
+                .into_iter()
+                .filter(|record| record.category == category && record.counts())
+                .map(|record| record.raw_wpm)
+                .collect(),
+        };
+        if wpms.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no tests in the history for {} yet to pace", category),
+            ));
+        }
+        let wpm = match target {
+            PaceTarget::Best => wpms.iter().copied().fold(0., f64::max),
+            _ => wpms.iter().sum::<f64>() / wpms.len() as f64,
+        };
+        Ok(Pace { wpm })
+    }
+
+    fn chars_per_second(&self) -> f64 {
+        self.wpm * 5. / 60.
+    }
+
+    // chars the pace has gone through at this point of the test
+    fn chars(&self, test: &TypingTest) -> usize {
+        let elapsed = test.elapsed().unwrap_or_default();
+        ((elapsed.as_secs_f64() * self.chars_per_second()) as usize).min(test.text_len())
+    }
+
+    pub fn position(&self, test: &TypingTest) -> (usize, usize) {
+        test.position_of(self.chars(test))
+    }
+
+    pub fn margin(&self, test: &TypingTest) -> String {
+        let finished = Duration::from_secs_f64(test.text_len() as f64 / self.chars_per_second());
+        ghost::margin(test, "the pace", self.chars(test), Some(finished))
+    }
+}
@@ -0,0 +1,162 @@
+use crate::runs::Run;
+use crate::typing_test::{Rules, TypingTest};
+use crossterm::{
+    cursor,
+    event::{poll, read, Event, KeyCode},
+    queue,
+    style::{PrintStyledContent, StyledContent, Stylize},
+    terminal,
+};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+pub const SPEEDS: [f64; 6] = [0.5, 1., 1.5, 2., 3., 4.];
+const SEEK_STEP: Duration = Duration::from_secs(1);
+const HINT: &str = "space: pause, left/right: seek, up/down: speed, home: restart, q: quit";
+
+// a run typed into a test again, up to any point of its time
+pub struct Player {
+    run: Run,
+    rules: Rules,
+    pub test: TypingTest,
+    // how many keys of the run are typed into the test
+    pub next_key: usize,
+    pub position: Duration,
+    pub length: Duration,
+}
+
+impl Player {
+    pub fn new(run: Run) -> Player {
+        let rules = run.rules();
+        // runs end on time, or with the last key when the text was done
+        let last_key = run.keys.last().map_or(Duration::ZERO, |&(time, _)| time);
+        let length = Duration::from_secs_f64(run.seconds).max(last_key);
+        Player {
+            test: TypingTest::new(&run.text, rules),
+            run,
+            rules,
+            next_key: 0,
+            position: Duration::ZERO,
+            length,
+        }
+    }
+
+    // jump to a point of the run, going back types the run again from the start
+    pub fn seek(&mut self, position: Duration) {
+        let position = position.min(self.length);
+        if position < self.position {
+            self.test = TypingTest::new(&self.run.text, self.rules);
+            self.next_key = 0;
+        }
+        self.position = position;
+        while let Some(&(time, key)) = self.run.keys.get(self.next_key) {
+            if time > position {
+                break;
+            }
+            self.test.press(key);
+            self.next_key += 1;
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.length
+    }
+}
+
+// play a run back in the terminal, which has to be in raw mode already
+pub fn play(run: Run, speed: f64) -> io::Result<()> {
+    let (mut width, mut height) = terminal::size()?;
+    let mut player = Player::new(run);
+    let mut speed = speed;
+    let mut paused = false;
+    let mut redraw = true;
+    let mut last_tick = Instant::now();
+
+    loop {
+        let now = Instant::now();
+        if !paused {
+            let keys_before = player.next_key;
+            player.seek(player.position + now.duration_since(last_tick).mul_f64(speed));
+            redraw |= player.next_key != keys_before;
+            paused = player.at_end();
+        }
+        last_tick = now;
+
+        if redraw {
+            queue!(io::stdout(), terminal::Clear(terminal::ClearType::All))?;
+            player.test.draw(&mut io::stdout(), width, height, &[])?;
+            draw_line(HINT.to_string().dark_grey(), height - 1)?;
+            redraw = false;
+        }
+        let status = format!(
+            "{:.1}s / {:.1}s  {}x{}",
+            player.position.as_secs_f64(),
+            player.length.as_secs_f64(),
+            speed,
+            if paused { "  paused" } else { "" }
+        );
+        draw_line(status.yellow(), 1)?;
+        io::stdout().flush()?;
+
+        if !poll(Duration::from_millis(50))? {
+            continue;
+        }
+        match read()? {
+            Event::Resize(w, h) => {
+                width = w;
+                height = h;
+                redraw = true;
+            }
+            Event::Key(keyevent) => match keyevent.code {
+                KeyCode::Char(' ') => {
+                    // playing on from the end starts over
+                    if paused && player.at_end() {
+                        player.seek(Duration::ZERO);
+                        redraw = true;
+                    }
+                    paused = !paused;
+                }
+                KeyCode::Left => {
+                    player.seek(player.position.saturating_sub(SEEK_STEP));
+                    redraw = true;
+                }
+                KeyCode::Right => {
+                    player.seek(player.position + SEEK_STEP);
+                    redraw = true;
+                }
+                KeyCode::Up => {
+                    speed = SPEEDS
+                        .into_iter()
+                        .find(|&faster| faster > speed)
+                        .unwrap_or(speed);
+                }
+                KeyCode::Down => {
+                    speed = SPEEDS
+                        .into_iter()
+                        .rev()
+                        .find(|&slower| slower < speed)
+                        .unwrap_or(speed);
+                }
+                KeyCode::Home => {
+                    player.seek(Duration::ZERO);
+                    redraw = true;
+                }
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+// a line of the screen replaced, keeping the cursor where it is in the text
+fn draw_line(line: StyledContent<String>, row: u16) -> io::Result<()> {
+    queue!(
+        io::stdout(),
+        cursor::SavePosition,
+        cursor::MoveTo(1, row),
+        terminal::Clear(terminal::ClearType::CurrentLine),
+        PrintStyledContent(line),
+        cursor::RestorePosition
+    )
+}
@@ -0,0 +1,385 @@
+use crate::chart::{self, Series};
+use crate::history::{self, Record};
+use crate::keyboard::{Keyboard, Metric};
+use crate::stats::{self, KeyStats, Second};
+use crate::typing_test::{CharStats, Rules, TypingTest};
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, Stylize},
+    terminal,
+};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy)]
+pub enum View {
+    Chart,
+    Errors,
+    Latency,
+    Ngrams,
+    Fingers,
+}
+
+impl View {
+    pub fn next(self) -> View {
+        match self {
+            View::Chart => View::Errors,
+            View::Errors => View::Latency,
+            View::Latency => View::Ngrams,
+            View::Ngrams => View::Fingers,
+            View::Fingers => View::Chart,
+        }
+    }
+}
+
+pub enum Outcome {
+    Passed,
+    // with the reason
+    Failed(String),
+    // ended with esc before it could pass or fail
+    Aborted,
+}
+
+impl Outcome {
+    // aborted tests neither passed nor failed
+    pub fn passed(&self) -> Option<bool> {
+        match self {
+            Outcome::Passed => Some(true),
+            Outcome::Failed(_) => Some(false),
+            Outcome::Aborted => None,
+        }
+    }
+}
+
+// how wide the block of results is, to center it on the screen
+const RESULTS_WIDTH: u16 = 60;
+// size of the wpm chart in terminal cells
+const CHART_WIDTH: usize = 50;
+const CHART_HEIGHT: usize = 8;
+// the chart shrinks down to this on short terminals
+const MIN_CHART_HEIGHT: usize = 3;
+// the legend above the chart and the error markers below it
+const CHART_EXTRA_ROWS: usize = 2;
+// space between the numbers and the view when they are side by side
+const COLUMN_GAP: usize = 4;
+const SLOWEST_WORDS: usize = 3;
+
+pub struct Results {
+    pub seconds: f64,
+    pub words_typed: usize,
+    pub chars_typed: usize,
+    pub wpm: f64,
+    pub raw_wpm: f64,
+    pub accuracy: f64,
+    pub errors: usize,
+    pub chars: CharStats,
+    pub timeline: Vec<Second>,
+    pub consistency: f64,
+    // fastest single word
+    pub burst_wpm: f64,
+    pub slowest_words: Vec<(String, f64)>,
+    pub keys: BTreeMap<char, KeyStats>,
+    pub bigrams: BTreeMap<String, KeyStats>,
+    pub trigrams: BTreeMap<String, KeyStats>,
+    pub category: String,
+    // only set in modes that can fail
+    pub outcome: Option<Outcome>,
+    failure: Option<&'static str>,
+    // ended with esc, the speed of a few words doesn't say much
+    pub aborted: bool,
+    pub pastes_rejected: usize,
+    // best wpm in the same category, this test included
+    pub best_wpm: Option<f64>,
+    // percentage, offset and length in chars
+    pub book: Option<(f64, usize, usize)>,
+    // how the lesson went, if the text was one
+    pub lesson: Option<String>,
+    // how the race against a ghost went
+    pub race: Option<String>,
+    // where the test ended compared to the pace caret
+    pub pace: Option<String>,
+    // where the run was saved to be replayed
+    pub replay: Option<PathBuf>,
+}
+
+impl Results {
+    pub fn new(test: &TypingTest, rules: &Rules, pastes_rejected: usize, aborted: bool) -> Results {
+        // millis instead of second for higher accuracy, at least one to not divide by zero
+        let time_typed = test
+            .elapsed()
+            .map_or(0, |elapsed| elapsed.as_millis())
+            .max(1);
+
+        let words_typed = test.words_typed();
+        let chars_typed = test.chars_typed();
+        let timeline = stats::per_second(&test.keystrokes, test.elapsed().unwrap_or_default());
+        let mut word_speeds = stats::word_speeds(test);
+        word_speeds.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        Results {
+            seconds: time_typed as f64 / 1000.,
+            words_typed,
+            chars_typed,
+            wpm: words_typed as f64 * (60000. / time_typed as f64),
+            raw_wpm: chars_typed as f64 / 5. * (60000. / time_typed as f64),
+            accuracy: test.accuracy(),
+            errors: test.incorrect_keys,
+            chars: test.char_stats(),
+            consistency: stats::consistency(&timeline),
+            timeline,
+            burst_wpm: word_speeds.last().map_or(0., |(_, wpm)| *wpm),
+            slowest_words: word_speeds.into_iter().take(SLOWEST_WORDS).collect(),
+            keys: stats::key_stats(&test.keystrokes),
+            bigrams: stats::ngram_stats(&test.keystrokes, 2),
+            trigrams: stats::ngram_stats(&test.keystrokes, 3),
+            category: rules.category(),
+            outcome: rules.can_fail().then(|| match test.failure {
+                Some(failure) => Outcome::Failed(failure.reason(rules)),
+                None if aborted => Outcome::Aborted,
+                None => Outcome::Passed,
+            }),
+            failure: test.failure.map(|failure| failure.name()),
+            aborted,
+            pastes_rejected,
+            best_wpm: None,
+            book: None,
+            lesson: None,
+            race: None,
+            pace: None,
+            replay: None,
+        }
+    }
+
+    pub fn record(&self) -> Record {
+        Record {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            category: self.category.clone(),
+            seconds: self.seconds,
+            wpm: self.wpm,
+            raw_wpm: self.raw_wpm,
+            accuracy: self.accuracy,
+            errors: self.errors,
+            incorrect_chars: self.chars.incorrect,
+            extra_chars: self.chars.extra,
+            missed_chars: self.chars.missed,
+            consistency: self.consistency,
+            burst_wpm: self.burst_wpm,
+            slowest_words: self.slowest_words.clone(),
+            keys: self.keys.clone(),
+            bigrams: self.bigrams.clone(),
+            trigrams: self.trigrams.clone(),
+            passed: self.outcome.as_ref().and_then(Outcome::passed),
+            failure: self.failure.map(str::to_string),
+            aborted: self.aborted,
+        }
+    }
+
+    // save the test and look up the best one of its category
+    pub fn save(&mut self) -> io::Result<()> {
+        let record = self.record();
+        history::append(&record)?;
+
+        self.best_wpm = Some(
+            history::load()?
+                .into_iter()
+                .filter(|old| old.category == record.category && old.counts())
+                .map(|old| old.wpm)
+                .fold(0., f64::max),
+        );
+        Ok(())
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(" time typed: {}", self.seconds as u64),
+            format!("words typed: {}", self.words_typed),
+            format!("chars typed: {}", self.chars_typed),
+            format!("   pure wpm: {:.2}", self.wpm),
+            format!("    raw wpm: {:.2}", self.raw_wpm),
+            format!("   accuracy: {:.2}%", self.accuracy),
+            format!("     errors: {}", self.errors),
+            format!(
+                "      chars: {} correct, {} incorrect, {} extra, {} missed",
+                self.chars.correct, self.chars.incorrect, self.chars.extra, self.chars.missed
+            ),
+            format!("consistency: {:.2}%", self.consistency),
+            format!("  burst wpm: {:.2}", self.burst_wpm),
+            format!(
+                " slow words: {}",
+                self.slowest_words
+                    .iter()
+                    .map(|(word, wpm)| format!("{} ({:.0} wpm)", word, wpm))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            format!("       mode: {}", self.category),
+        ];
+        match &self.outcome {
+            Some(Outcome::Passed) => lines.push(format!("     result: {}", "passed".green())),
+            Some(Outcome::Failed(reason)) => {
+                lines.push(format!("     result: {} ({})", "failed".red(), reason))
+            }
+            Some(Outcome::Aborted) => lines.push(format!("     result: {}", "aborted".yellow())),
+            None => {}
+        }
+        if self.pastes_rejected > 0 {
+            lines.push(format!(
+                "     pasted: {} times, rejected",
+                self.pastes_rejected
+            ));
+        }
+        if let Some(best_wpm) = self.best_wpm {
+            lines.push(format!("   best wpm: {:.2}", best_wpm));
+        }
+        if let Some((progress, offset, len)) = self.book {
+            lines.push(format!(
+                "  book done: {:.2}% ({}/{} chars)",
+                progress, offset, len
+            ));
+        }
+        if let Some(lesson) = &self.lesson {
+            lines.push(format!("     lesson: {}", lesson));
+        }
+        if let Some(race) = &self.race {
+            lines.push(format!("       race: {}", race));
+        }
+        if let Some(pace) = &self.pace {
+            lines.push(format!("       pace: {}", pace));
+        }
+        if let Some(replay) = &self.replay {
+            lines.push(format!("     replay: {}", replay.display()));
+        }
+
+        lines
+    }
+
+    // what is shown below the numbers, switched through on the results screen
+    pub fn view_lines(&self, view: View, layout: &Keyboard) -> Vec<String> {
+        match view {
+            View::Chart => self.chart_lines(CHART_HEIGHT),
+            View::Errors => layout.heatmap(&self.keys, Metric::Errors),
+            View::Latency => layout.heatmap(&self.keys, Metric::Latency),
+            View::Ngrams => {
+                let mut lines = stats::ngram_lines("bigrams", &self.bigrams, 1);
+                lines.extend(stats::ngram_lines("trigrams", &self.trigrams, 1));
+                lines
+            }
+            View::Fingers => layout.finger_lines(&self.keys, &self.bigrams),
+        }
+    }
+
+    fn chart_lines(&self, height: usize) -> Vec<String> {
+        let mut lines = vec![format!(
+            "      {} raw wpm  {} wpm  {} errors",
+            "⠉".dark_grey(),
+            "⠉".green(),
+            "x".red()
+        )];
+        let raw_wpm: Vec<f64> = self.timeline.iter().map(|second| second.raw_wpm).collect();
+        let wpm: Vec<f64> = self.timeline.iter().map(|second| second.wpm).collect();
+        let errors: Vec<bool> = self
+            .timeline
+            .iter()
+            .map(|second| second.errors > 0)
+            .collect();
+        lines.extend(chart::line_chart(
+            &[
+                Series {
+                    values: &raw_wpm,
+                    color: Color::DarkGrey,
+                },
+                Series {
+                    values: &wpm,
+                    color: Color::Green,
+                },
+            ],
+            &errors,
+            CHART_WIDTH,
+            height,
+        ));
+
+        lines
+    }
+
+    // the numbers with the view below them, or beside them when that doesn't fit the height,
+    // what still doesn't fit is cut off so it doesn't run into the hint on the last row
+    pub fn draw(&self, width: u16, height: u16, view: View, layout: &Keyboard) -> io::Result<()> {
+        let rows = height.saturating_sub(1) as usize;
+        let numbers = self.lines();
+        let view_lines = self.view_lines(view, layout);
+        let numbers_width = numbers
+            .iter()
+            .map(|line| visible_width(line))
+            .max()
+            .unwrap_or(0);
+        let view_width = view_lines
+            .iter()
+            .map(|line| visible_width(line))
+            .max()
+            .unwrap_or(0);
+        let side_by_side_width = numbers_width + COLUMN_GAP + view_width;
+
+        // every row is a few lines, each with the column it starts at
+        let mut block: Vec<Vec<(usize, String)>> = Vec::new();
+        let block_width = if numbers.len() + 1 + view_lines.len() > rows
+            && side_by_side_width <= width as usize
+        {
+            let column = numbers_width + COLUMN_GAP;
+            for row in 0..numbers.len().max(view_lines.len()) {
+                let mut lines = Vec::new();
+                lines.extend(numbers.get(row).map(|line| (0, line.clone())));
+                lines.extend(view_lines.get(row).map(|line| (column, line.clone())));
+                block.push(lines);
+            }
+            side_by_side_width
+        } else {
+            let view_lines = match view {
+                // the chart gives up rows to fit, the other views can't
+                View::Chart => {
+                    let room = rows.saturating_sub(numbers.len() + 1 + CHART_EXTRA_ROWS);
+                    self.chart_lines(room.clamp(MIN_CHART_HEIGHT, CHART_HEIGHT))
+                }
+                _ => view_lines,
+            };
+            block.extend(numbers.into_iter().map(|line| vec![(0, line)]));
+            block.push(Vec::new());
+            block.extend(view_lines.into_iter().map(|line| vec![(0, line)]));
+            RESULTS_WIDTH as usize
+        };
+        block.truncate(rows);
+        let start_height = rows.saturating_sub(block.len()) / 2;
+        let start_width = (width as usize).saturating_sub(block_width) / 2;
+
+        queue!(io::stdout(), terminal::Clear(terminal::ClearType::All))?;
+        for (row, lines) in block.iter().enumerate() {
+            for (column, line) in lines {
+                queue!(
+                    io::stdout(),
+                    cursor::MoveTo((start_width + column) as u16, (start_height + row) as u16),
+                    Print(line)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// columns a line takes up on screen, without its color codes
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            // color codes end with a letter
+            c if in_escape => in_escape = !c.is_ascii_alphabetic(),
+            _ => width += 1,
+        }
+    }
+    width
+}
@@ -0,0 +1,259 @@
+use crate::results::{Outcome, Results};
+use crate::storage;
+use crate::typing_test::{Key, Rules, TypingTest};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RUNS_DIR: &str = "runs";
+const RUN_EXTENSION: &str = "run";
+// older runs are deleted so the directory doesn't grow forever
+const MAX_RUNS: usize = 100;
+
+// a test as it was typed, key by key, to race against or watch again
+pub struct Run {
+    pub wpm: f64,
+    pub seconds: f64,
+    // the rules it was typed with
+    pub category: String,
+    // only set in modes that can fail
+    pub passed: Option<bool>,
+    // ended with esc
+    pub aborted: bool,
+    // what the text was generated from, if it was
+    pub seed: Option<u64>,
+    pub text: String,
+    // keys with the time since the first one
+    pub keys: Vec<(Duration, Key)>,
+}
+
+impl Run {
+    pub fn new(test: &TypingTest, text: &str, results: &Results, seed: Option<u64>) -> Run {
+        Run {
+            wpm: results.wpm,
+            seconds: results.seconds,
+            category: results.category.clone(),
+            passed: results.outcome.as_ref().and_then(Outcome::passed),
+            aborted: results.aborted,
+            seed,
+            // the test splits the text at any whitespace, so a line of it is the same text
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            keys: test
+                .keystrokes
+                .iter()
+                .map(|keystroke| (keystroke.time, keystroke.key))
+                .collect(),
+        }
+    }
+
+    // the rules to type the text with again, normal ones if they can't be read
+    pub fn rules(&self) -> Rules {
+        Rules::from_category(&self.category).unwrap_or_default()
+    }
+
+    // a header line of tab separated key=value pairs like the history, the text on the second
+    // line and a key with its time in milliseconds on every line after that
+    fn to_file(&self) -> String {
+        let mut header = vec![
+            format!("wpm={:.2}", self.wpm),
+            format!("seconds={:.3}", self.seconds),
+            format!("category={}", self.category),
+        ];
+        if let Some(passed) = self.passed {
+            header.push(format!("passed={}", passed));
+        }
+        if self.aborted {
+            header.push("aborted=true".to_string());
+        }
+        if let Some(seed) = self.seed {
+            header.push(format!("seed={}", seed));
+        }
+        let mut content = format!("{}\n{}\n", header.join("\t"), self.text);
+        for (time, key) in &self.keys {
+            let key = match key {
+                Key::Char(c) => c.to_string(),
+                Key::Backspace => "backspace".to_string(),
+                Key::DeleteWord => "delete-word".to_string(),
+            };
+            content.push_str(&format!("{}\t{}\n", time.as_millis(), key));
+        }
+        content
+    }
+
+    fn from_file(content: &str) -> Option<Run> {
+        let mut lines = content.lines();
+        let mut run = Run {
+            wpm: 0.,
+            seconds: 0.,
+            category: Rules::default().category(),
+            passed: None,
+            aborted: false,
+            seed: None,
+            text: String::new(),
+            keys: Vec::new(),
+        };
+        for (key, value) in lines
+            .next()?
+            .split('\t')
+            .filter_map(|field| field.split_once('='))
+        {
+            match key {
+                "wpm" => run.wpm = value.parse().ok()?,
+                "seconds" => run.seconds = value.parse().ok()?,
+                "category" => run.category = value.to_string(),
+                "passed" => run.passed = Some(value.parse().ok()?),
+                "aborted" => run.aborted = value.parse().ok()?,
+                "seed" => run.seed = Some(value.parse().ok()?),
+                _ => {}
+            }
+        }
+        run.text = lines.next()?.to_string();
+        for line in lines {
+            let (time, key) = line.split_once('\t')?;
+            let key = match key {
+                "backspace" => Key::Backspace,
+                "delete-word" => Key::DeleteWord,
+                _ => Key::Char(key.chars().next()?),
+            };
+            run.keys
+                .push((Duration::from_millis(time.parse().ok()?), key));
+        }
+        Some(run)
+    }
+
+    // save the run under the current time and drop the oldest ones over the limit
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let dir = runs_dir()?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let path = dir.join(format!("{}.{}", millis, RUN_EXTENSION));
+        fs::write(&path, self.to_file())?;
+
+        let mut paths = run_paths()?;
+        // the names are timestamps of the same length, so they sort by age
+        paths.sort();
+        // the best run of every mode stays, or racing the best would only race the latest ones
+        let best: Vec<PathBuf> = best_runs()?.into_values().map(|(path, _)| path).collect();
+        for old in paths.iter().rev().skip(MAX_RUNS) {
+            if !best.contains(old) {
+                fs::remove_file(old)?;
+            }
+        }
+        Ok(path)
+    }
+
+    // a test ended after a word or two, or one that failed, is no run to beat
+    fn can_be_raced(&self) -> bool {
+        !self.aborted && self.passed != Some(false)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Run> {
+        Run::from_file(&fs::read_to_string(path)?).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a run", path.display()),
+            )
+        })
+    }
+}
+
+// the saved run of a category with the highest wpm
+pub fn best(category: &str) -> io::Result<Option<Run>> {
+    Ok(best_runs()?.remove(category).map(|(_, run)| run))
+}
+
+// the saved run with the highest wpm of every category, with its path
+fn best_runs() -> io::Result<BTreeMap<String, (PathBuf, Run)>> {
+    let mut best: BTreeMap<String, (PathBuf, Run)> = BTreeMap::new();
+    for path in run_paths()? {
+        // a broken file shouldn't keep the others from being raced
+        let Ok(run) = Run::load(&path) else {
+            continue;
+        };
+        if !run.can_be_raced() {
+            continue;
+        }
+        if best
+            .get(&run.category)
+            .is_none_or(|(_, best)| run.wpm > best.wpm)
+        {
+            best.insert(run.category.clone(), (path, run));
+        }
+    }
+    Ok(best)
+}
+
+fn runs_dir() -> io::Result<PathBuf> {
+    let dir = storage::data_dir()?.join(RUNS_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn run_paths() -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(runs_dir()?)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == RUN_EXTENSION)
+        {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_round_trip() {
+        let run = Run {
+            wpm: 80.5,
+            seconds: 9.875,
+            category: "word-input+sudden-death".to_string(),
+            passed: Some(true),
+            aborted: true,
+            seed: Some(u64::MAX),
+            text: "ab cd: ef".to_string(),
+            keys: vec![
+                (Duration::ZERO, Key::Char('a')),
+                (Duration::from_millis(120), Key::Char(' ')),
+                (Duration::from_millis(250), Key::Backspace),
+                (Duration::from_millis(400), Key::DeleteWord),
+                (Duration::from_millis(610), Key::Char('=')),
+            ],
+        };
+        let file = run.to_file();
+        let parsed = Run::from_file(&file).unwrap();
+        assert_eq!(parsed.to_file(), file);
+        assert!(parsed.keys == run.keys);
+        assert_eq!(parsed.rules().category(), run.category);
+        assert!(!parsed.can_be_raced());
+    }
+
+    #[test]
+    fn only_whole_runs_can_be_raced() {
+        let run = Run::from_file("wpm=40.00\tcategory=normal\nab cd\n").unwrap();
+        assert!(run.can_be_raced());
+        let failed = Run::from_file("wpm=40.00\tpassed=false\nab cd\n").unwrap();
+        assert!(!failed.can_be_raced());
+        let aborted = Run::from_file("wpm=400.00\taborted=true\nab cd\n").unwrap();
+        assert!(!aborted.can_be_raced());
+    }
+
+    #[test]
+    fn older_runs_load() {
+        let run = Run::from_file("wpm=40.00\tcategory=normal\nab cd\n0\ta\n").unwrap();
+        assert_eq!(run.passed, None);
+        assert!(!run.aborted);
+        assert_eq!(run.seed, None);
+        assert_eq!(run.keys.len(), 1);
+        assert!(Run::from_file("wpm=40.00\nab cd\n0\n").is_none());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::typing_test::{Key, Keystroke, TypingTest};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// longer gaps between keys are pauses, not the time it takes to find a key
+const MAX_LATENCY: Duration = Duration::from_secs(2);
+const WORST_NGRAMS: usize = 5;
+
+pub struct Second {
+    // speed of the keys typed in this second alone
+    pub raw_wpm: f64,
+    // speed of the correct keys from the start up to the end of this second
+    pub wpm: f64,
+    pub errors: usize,
+}
+
+// one sample per started second of the test, the last one may be shorter
+pub fn per_second(keystrokes: &[Keystroke], duration: Duration) -> Vec<Second> {
+    let seconds = (duration.as_secs_f64().ceil() as usize).max(1);
+    let mut keys = vec![0; seconds];
+    let mut correct_keys = vec![0; seconds];
+    let mut errors = vec![0; seconds];
+
+    for keystroke in keystrokes {
+        if !matches!(keystroke.key, Key::Char(_)) {
+            continue;
+        }
+        let second = (keystroke.time.as_secs() as usize).min(seconds - 1);
+        keys[second] += 1;
+        if keystroke.correct {
+            correct_keys[second] += 1;
+        } else {
+            errors[second] += 1;
+        }
+    }
+
+    let mut samples = Vec::with_capacity(seconds);
+    let mut correct_so_far = 0;
+    for second in 0..seconds {
+        let end = ((second + 1) as f64).min(duration.as_secs_f64()).max(0.001);
+        let length = (end - second as f64).max(0.001);
+        correct_so_far += correct_keys[second];
+        samples.push(Second {
+            raw_wpm: keys[second] as f64 / 5. * (60. / length),
+            wpm: correct_so_far as f64 / 5. * (60. / end),
+            errors: errors[second],
+        });
+    }
+    samples
+}
+
+// how steady the speed was from second to second, 100% means exactly the same speed all along
+pub fn consistency(timeline: &[Second]) -> f64 {
+    // a last second that was cut short says little about the speed
+    let samples: Vec<f64> = match timeline {
+        [complete @ .., _] if complete.len() >= 2 => complete,
+        _ => timeline,
+    }
+    .iter()
+    .map(|second| second.raw_wpm)
+    .collect();
+
+    let mean = samples.iter().sum::<f64>() / samples.len().max(1) as f64;
+    if mean == 0. {
+        return 0.;
+    }
+    let variance = samples
+        .iter()
+        .map(|sample| (sample - mean).powi(2))
+        .sum::<f64>()
+        / samples.len() as f64;
+    let coefficient_of_variation = variance.sqrt() / mean;
+    (1. - coefficient_of_variation).max(0.) * 100.
+}
+
+// wpm of every word typed correctly, from the key that ended the word before it
+// to the key that ended it
+pub fn word_speeds(test: &TypingTest) -> Vec<(String, f64)> {
+    let mut word_ends: Vec<Option<Duration>> = Vec::new();
+    for keystroke in &test.keystrokes {
+        if matches!(keystroke.key, Key::Char(_)) {
+            if word_ends.len() <= keystroke.word {
+                word_ends.resize(keystroke.word + 1, None);
+            }
+            word_ends[keystroke.word] = Some(keystroke.time);
+        }
+    }
+
+    let mut speeds = Vec::new();
+    for (index, word, correct) in test.finished_words() {
+        let Some(Some(end)) = word_ends.get(index) else {
+            continue;
+        };
+        // the first key starts the timer, so it takes no time
+        let (start, keys) = match index {
+            0 => (Duration::ZERO, word.len()),
+            _ => match word_ends.get(index - 1) {
+                Some(Some(start)) => (*start, word.len() + 1),
+                _ => continue,
+            },
+        };
+        let minutes = end.saturating_sub(start).as_secs_f64() / 60.;
+        if correct && minutes > 0. {
+            speeds.push((word.iter().collect(), keys as f64 / 5. / minutes));
+        }
+    }
+    speeds
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct KeyStats {
+    // times the key was asked for by the text
+    pub presses: usize,
+    pub errors: usize,
+    // summed up time since the key before, over the presses that had a key before them
+    pub latency: Duration,
+    pub timed: usize,
+}
+
+impl KeyStats {
+    pub fn add(&mut self, other: &KeyStats) {
+        self.presses += other.presses;
+        self.errors += other.errors;
+        self.latency += other.latency;
+        self.timed += other.timed;
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        self.errors as f64 / self.presses.max(1) as f64
+    }
+
+    pub fn average_latency(&self) -> Option<Duration> {
+        (self.timed > 0).then(|| self.latency / self.timed as u32)
+    }
+}
+
+// accuracy and speed of every char the text asked for
+pub fn key_stats(keystrokes: &[Keystroke]) -> BTreeMap<char, KeyStats> {
+    let mut stats: BTreeMap<char, KeyStats> = BTreeMap::new();
+    let mut last_time = None;
+
+    for keystroke in keystrokes {
+        let time_before = last_time.replace(keystroke.time);
+        let Some(expected) = keystroke.expected else {
+            continue;
+        };
+        let key_stats = stats.entry(expected).or_default();
+        key_stats.presses += 1;
+        if !keystroke.correct {
+            key_stats.errors += 1;
+        }
+        let latency = time_before.map(|time_before| keystroke.time.saturating_sub(time_before));
+        if let Some(latency) = latency.filter(|latency| *latency <= MAX_LATENCY) {
+            key_stats.latency += latency;
+            key_stats.timed += 1;
+        }
+    }
+
+    stats
+}
+
+// time and errors of every run of n chars in a row, the time runs from the first key to the
+// last and an error means the last key was wrong, spaces and backspaces break the runs
+pub fn ngram_stats(keystrokes: &[Keystroke], n: usize) -> BTreeMap<String, KeyStats> {
+    let mut stats: BTreeMap<String, KeyStats> = BTreeMap::new();
+    let mut run: Vec<&Keystroke> = Vec::with_capacity(n);
+
+    for keystroke in keystrokes {
+        if keystroke.expected.is_none_or(|expected| expected == ' ') {
+            run.clear();
+            continue;
+        }
+        if run.len() == n {
+            run.remove(0);
+        }
+        run.push(keystroke);
+        if run.len() == n {
+            let ngram: String = run
+                .iter()
+                .filter_map(|keystroke| keystroke.expected)
+                .collect();
+            let ngram_stats = stats.entry(ngram).or_default();
+            ngram_stats.presses += 1;
+            if !keystroke.correct {
+                ngram_stats.errors += 1;
+            }
+            let time = keystroke.time.saturating_sub(run[0].time);
+            if time <= MAX_LATENCY * (n as u32 - 1) {
+                ngram_stats.latency += time;
+                ngram_stats.timed += 1;
+            }
+        }
+        // the next run starts over, the key after a wrong one is a new attempt
+        if !keystroke.correct {
+            run.clear();
+        }
+    }
+
+    stats
+}
+
+// the slowest and most missed ngrams that were typed at least min_count times
+pub fn ngram_lines(
+    name: &str,
+    stats: &BTreeMap<String, KeyStats>,
+    min_count: usize,
+) -> Vec<String> {
+    let mut slowest: Vec<(&String, Duration)> = stats
+        .iter()
+        .filter(|(_, stats)| stats.presses >= min_count)
+        .filter_map(|(ngram, stats)| Some((ngram, stats.average_latency()?)))
+        .collect();
+    slowest.sort_by_key(|&(_, latency)| std::cmp::Reverse(latency));
+
+    let mut most_missed: Vec<(&String, f64)> = stats
+        .iter()
+        .filter(|(_, stats)| stats.presses >= min_count && stats.errors > 0)
+        .map(|(ngram, stats)| (ngram, stats.error_rate()))
+        .collect();
+    most_missed.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    vec![
+        format!(
+            "slowest {}: {}",
+            name,
+            slowest
+                .iter()
+                .take(WORST_NGRAMS)
+                .map(|(ngram, latency)| format!("{} {}ms", ngram, latency.as_millis()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        format!(
+            "most missed {}: {}",
+            name,
+            most_missed
+                .iter()
+                .take(WORST_NGRAMS)
+                .map(|(ngram, rate)| format!("{} {:.0}%", ngram, rate * 100.))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystroke(millis: u64, expected: char, typed: char) -> Keystroke {
+        Keystroke {
+            time: Duration::from_millis(millis),
+            key: Key::Char(typed),
+            word: 0,
+            expected: Some(expected),
+            correct: typed == expected,
+        }
+    }
+
+    #[test]
+    fn samples_per_second() {
+        let keystrokes = [
+            keystroke(0, 'a', 'a'),
+            keystroke(500, 'b', 'b'),
+            Keystroke {
+                time: Duration::from_millis(800),
+                key: Key::Backspace,
+                word: 0,
+                expected: None,
+                correct: true,
+            },
+            keystroke(1000, 'c', 'x'),
+            keystroke(1500, 'd', 'd'),
+        ];
+        let timeline = per_second(&keystrokes, Duration::from_millis(1500));
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].raw_wpm, 24.);
+        assert_eq!(timeline[0].wpm, 24.);
+        assert_eq!(timeline[0].errors, 0);
+        // the last second was half as long
+        assert_eq!(timeline[1].raw_wpm, 48.);
+        assert_eq!(timeline[1].wpm, 24.);
+        assert_eq!(timeline[1].errors, 1);
+
+        assert_eq!(per_second(&[], Duration::ZERO).len(), 1);
+    }
+
+    fn seconds(raw_wpms: &[f64]) -> Vec<Second> {
+        raw_wpms
+            .iter()
+            .map(|&raw_wpm| Second {
+                raw_wpm,
+                wpm: 0.,
+                errors: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consistency_of_the_speed() {
+        // the last second is left out, it can be cut short
+        assert_eq!(consistency(&seconds(&[60., 60., 60., 5.])), 100.);
+        let uneven = consistency(&seconds(&[40., 80., 0.]));
+        assert!((uneven - 100. * 2. / 3.).abs() < 1e-9);
+        assert_eq!(consistency(&seconds(&[0., 0.])), 0.);
+        assert_eq!(consistency(&seconds(&[10., 200., 0., 300.])), 0.);
+    }
+
+    #[test]
+    fn ngrams() {
+        let keystrokes = [
+            keystroke(0, 'a', 'a'),
+            keystroke(100, 'b', 'b'),
+            keystroke(200, ' ', ' '),
+            keystroke(300, 'c', 'c'),
+            keystroke(700, 'd', 'x'),
+            // a wrong key starts the run over
+            keystroke(800, 'e', 'e'),
+            keystroke(3900, 'f', 'f'),
+        ];
+        let bigrams = ngram_stats(&keystrokes, 2);
+        assert_eq!(bigrams.keys().collect::<Vec<_>>(), ["ab", "cd", "ef"]);
+        assert_eq!(
+            bigrams["ab"].average_latency(),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!((bigrams["cd"].presses, bigrams["cd"].errors), (1, 1));
+        assert_eq!(bigrams["cd"].latency, Duration::from_millis(400));
+        // a pause isn't how long the keys take
+        assert_eq!(bigrams["ef"].average_latency(), None);
+        assert!(ngram_stats(&keystrokes, 3).is_empty());
+
+        let abc = [
+            keystroke(0, 'a', 'a'),
+            keystroke(100, 'b', 'b'),
+            keystroke(250, 'c', 'c'),
+        ];
+        let trigrams = ngram_stats(&abc, 3);
+        assert_eq!(trigrams["abc"].latency, Duration::from_millis(250));
+    }
+}
@@ -0,0 +1,47 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// everything tylee remembers between sessions lives in one directory,
+// $XDG_DATA_HOME/tylee or ~/.local/share/tylee
+pub fn data_dir() -> io::Result<PathBuf> {
+    let base = match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".local").join("share"),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "neither XDG_DATA_HOME nor HOME is set",
+                ))
+            }
+        },
+    };
+    let dir = base.join("tylee");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// read a data file, a missing file is the same as an empty one
+pub fn read_data_file(name: &str) -> io::Result<String> {
+    match fs::read_to_string(data_dir()?.join(name)) {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err),
+    }
+}
+
+pub fn write_data_file(name: &str, content: &str) -> io::Result<()> {
+    fs::write(data_dir()?.join(name), content)
+}
+
+// 64 bit FNV-1a, stable across rust versions unlike DefaultHasher
+pub fn content_hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
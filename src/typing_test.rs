@@ -0,0 +1,839 @@
+use crossterm::{
+    cursor, queue,
+    style::{Color, PrintStyledContent, StyledContent, Stylize},
+    terminal,
+};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum StopOnError {
+    #[default]
+    Off,
+    // a wrong key is counted but the cursor stays until the right one is typed
+    Letter,
+    // a word has to be typed correctly before moving past its trailing space
+    Word,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Input {
+    // every key is compared against the next char of the text, space included
+    #[default]
+    Chars,
+    // space always jumps to the next word, extra keys stay in the current one
+    Words,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Backspace {
+    #[default]
+    Allowed,
+    // only within the current word, finished words can't be corrected anymore
+    Confidence,
+    Disabled,
+}
+
+// keys typed before a minimum accuracy is enforced, so the first typo doesn't end the test
+const ACCURACY_GRACE_KEYS: usize = 10;
+// extra chars one word can hold in word input, so the text doesn't grow forever
+const MAX_EXTRA_CHARS: usize = 10;
+
+// how a test reacts to the keys typed
+#[derive(Clone, Copy, Default)]
+pub struct Rules {
+    pub input: Input,
+    pub stop_on_error: StopOnError,
+    pub backspace: Backspace,
+    // the first error fails the test
+    pub sudden_death: bool,
+    // accuracy in percent the test fails below
+    pub min_accuracy: Option<f64>,
+}
+
+impl Rules {
+    // name of the mode, tests are only compared within the same category
+    pub fn category(&self) -> String {
+        let mut modes = Vec::new();
+        if self.input == Input::Words {
+            modes.push("word-input".to_string());
+        }
+        match self.stop_on_error {
+            StopOnError::Off => {}
+            StopOnError::Letter => modes.push("strict".to_string()),
+            StopOnError::Word => modes.push("stop-on-word".to_string()),
+        }
+        match self.backspace {
+            Backspace::Allowed => {}
+            Backspace::Confidence => modes.push("confidence".to_string()),
+            Backspace::Disabled => modes.push("no-backspace".to_string()),
+        }
+        if self.sudden_death {
+            modes.push("sudden-death".to_string());
+        }
+        if let Some(min_accuracy) = self.min_accuracy {
+            modes.push(format!("accuracy-{}", min_accuracy));
+        }
+
+        if modes.is_empty() {
+            "normal".to_string()
+        } else {
+            modes.join("+")
+        }
+    }
+
+    // the rules a category stands for, the other way around of category
+    pub fn from_category(category: &str) -> Option<Rules> {
+        let mut rules = Rules::default();
+        if category == "normal" {
+            return Some(rules);
+        }
+        for mode in category.split('+') {
+            match mode {
+                "word-input" => rules.input = Input::Words,
+                "strict" => rules.stop_on_error = StopOnError::Letter,
+                "stop-on-word" => rules.stop_on_error = StopOnError::Word,
+                "confidence" => rules.backspace = Backspace::Confidence,
+                "no-backspace" => rules.backspace = Backspace::Disabled,
+                "sudden-death" => rules.sudden_death = true,
+                _ => rules.min_accuracy = Some(mode.strip_prefix("accuracy-")?.parse().ok()?),
+            }
+        }
+        Some(rules)
+    }
+
+    // whether a test can fail at all
+    pub fn can_fail(&self) -> bool {
+        self.sudden_death || self.min_accuracy.is_some()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Failure {
+    FirstError,
+    // accuracy at the moment it dropped below the minimum
+    Accuracy(f64),
+}
+
+impl Failure {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Failure::FirstError => "first-error",
+            Failure::Accuracy(_) => "accuracy",
+        }
+    }
+
+    pub fn reason(&self, rules: &Rules) -> String {
+        match self {
+            Failure::FirstError => "made an error in sudden death".to_string(),
+            Failure::Accuracy(accuracy) => format!(
+                "accuracy dropped to {:.2}%, below {}%",
+                accuracy,
+                rules.min_accuracy.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CharStats {
+    pub correct: usize,
+    pub incorrect: usize,
+    // typed after the end of a word
+    pub extra: usize,
+    // never typed because the word was left early
+    pub missed: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    DeleteWord,
+}
+
+pub struct Keystroke {
+    // since the first key of the test
+    pub time: Duration,
+    pub key: Key,
+    // index of the word that was current when the key was typed
+    pub word: usize,
+    // the char the text asked for, only for chars
+    pub expected: Option<char>,
+    // only chars can be wrong
+    pub correct: bool,
+}
+
+pub struct TypingTest {
+    words: Vec<Vec<char>>,
+    // keys typed for every started word, the last one is the current word
+    typed: Vec<Vec<char>>,
+    // key typed in place of the space after each finished word
+    separators: Vec<char>,
+    rules: Rules,
+    // set by the first typed key
+    start: Option<Instant>,
+    // how long the test took, once it is over
+    end: Option<Duration>,
+    pub keystrokes: Vec<Keystroke>,
+    pub correct_keys: usize,
+    pub incorrect_keys: usize,
+    pub failure: Option<Failure>,
+}
+
+impl TypingTest {
+    pub fn new(text: &str, rules: Rules) -> TypingTest {
+        TypingTest {
+            words: text
+                .split_whitespace()
+                .map(|word| word.chars().collect())
+                .collect(),
+            typed: vec![Vec::new()],
+            separators: Vec::new(),
+            rules,
+            start: None,
+            end: None,
+            keystrokes: Vec::new(),
+            correct_keys: 0,
+            incorrect_keys: 0,
+            failure: None,
+        }
+    }
+
+    pub fn press(&mut self, key: Key) {
+        match key {
+            Key::Char(c) => self.type_key(c),
+            Key::Backspace => self.backspace(),
+            Key::DeleteWord => self.delete_word(),
+        }
+    }
+
+    pub fn type_key(&mut self, key: char) {
+        if self.is_finished() {
+            return;
+        }
+        self.start.get_or_insert_with(Instant::now);
+        match self.rules.input {
+            Input::Chars => self.type_char_key(key),
+            Input::Words => self.type_word_key(key),
+        }
+    }
+
+    fn type_char_key(&mut self, key: char) {
+        let word_index = self.typed.len() - 1;
+        let target = &self.words[word_index];
+        let typed = &self.typed[word_index];
+        let word_correct = typed == target;
+
+        if let Some(&expected) = target.get(typed.len()) {
+            let correct = key == expected;
+            self.count_key(key, correct);
+            if correct || self.rules.stop_on_error != StopOnError::Letter {
+                self.typed[word_index].push(key);
+            }
+            return;
+        }
+
+        // the last word has no space after it to type
+        if word_index == self.words.len() - 1 {
+            return;
+        }
+
+        let correct = key == ' ';
+        if self.rules.stop_on_error == StopOnError::Word && !word_correct {
+            // not allowed to leave a word with errors in it
+            self.count_key(key, false);
+            return;
+        }
+        self.count_key(key, correct);
+        if correct || self.rules.stop_on_error != StopOnError::Letter {
+            self.separators.push(key);
+            self.typed.push(Vec::new());
+        }
+    }
+
+    fn type_word_key(&mut self, key: char) {
+        let word_index = self.typed.len() - 1;
+        let target = &self.words[word_index];
+        let typed = &self.typed[word_index];
+
+        if key == ' ' {
+            // nothing to skip yet
+            if typed.is_empty() {
+                return;
+            }
+            let correct = typed == target;
+            self.count_key(key, correct);
+            if !correct && self.rules.stop_on_error != StopOnError::Off {
+                return;
+            }
+            self.separators.push(' ');
+            if word_index < self.words.len() - 1 {
+                self.typed.push(Vec::new());
+            }
+            return;
+        }
+
+        let correct = target.get(typed.len()) == Some(&key);
+        let has_room = typed.len() < target.len() + MAX_EXTRA_CHARS;
+        self.count_key(key, correct);
+        if (correct || self.rules.stop_on_error != StopOnError::Letter) && has_room {
+            self.typed[word_index].push(key);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.rules.backspace == Backspace::Disabled {
+            return;
+        }
+        self.log(Key::Backspace, None, true);
+        if self.typed.last_mut().unwrap().pop().is_none()
+            && self.typed.len() > 1
+            && self.rules.backspace != Backspace::Confidence
+        {
+            // step back over the space into the previous word
+            self.typed.pop();
+            self.separators.pop();
+        }
+    }
+
+    // clear the current word, or the previous one when the current word is still empty
+    pub fn delete_word(&mut self) {
+        if self.rules.backspace == Backspace::Disabled {
+            return;
+        }
+        self.log(Key::DeleteWord, None, true);
+        if self.typed.last().unwrap().is_empty() {
+            if self.typed.len() == 1 || self.rules.backspace == Backspace::Confidence {
+                return;
+            }
+            self.typed.pop();
+            self.separators.pop();
+        }
+        // errors made in the deleted keys stay counted, only the text is restored
+        self.typed.last_mut().unwrap().clear();
+    }
+
+    // word and char index of where the next key goes
+    pub fn position(&self) -> (usize, usize) {
+        (self.typed.len() - 1, self.typed.last().unwrap().len())
+    }
+
+    // word and char index of the char at an offset into the text, the end of the text at most
+    pub fn position_of(&self, mut offset: usize) -> (usize, usize) {
+        for (word_index, word) in self.words.iter().enumerate() {
+            if offset <= word.len() {
+                return (word_index, offset);
+            }
+            offset -= word.len() + 1;
+        }
+        let last = self.words.len() - 1;
+        (last, self.words[last].len())
+    }
+
+    // chars in the text, with the spaces between the words
+    pub fn text_len(&self) -> usize {
+        self.words.iter().map(|word| word.len() + 1).sum::<usize>() - 1
+    }
+
+    // the char the text asks for next
+    pub fn next_char(&self) -> char {
+        let word_index = self.typed.len() - 1;
+        // after the end of a word the space is next
+        self.words[word_index]
+            .get(self.typed[word_index].len())
+            .copied()
+            .unwrap_or(' ')
+    }
+
+    fn count_key(&mut self, key: char, correct: bool) {
+        let expected = self.next_char();
+        self.log(Key::Char(key), Some(expected), correct);
+        if correct {
+            self.correct_keys += 1;
+        } else {
+            self.incorrect_keys += 1;
+        }
+
+        if self.failure.is_some() {
+            return;
+        }
+        if !correct && self.rules.sudden_death {
+            self.failure = Some(Failure::FirstError);
+        } else if self.correct_keys + self.incorrect_keys >= ACCURACY_GRACE_KEYS {
+            self.check_accuracy();
+        }
+    }
+
+    fn log(&mut self, key: Key, expected: Option<char>, correct: bool) {
+        // keys before the test started can only be backspaces, nothing to remember
+        if let Some(time) = self.elapsed() {
+            self.keystrokes.push(Keystroke {
+                time,
+                key,
+                word: self.typed.len() - 1,
+                expected,
+                correct,
+            });
+        }
+    }
+
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.end.or(self.start.map(|start| start.elapsed()))
+    }
+
+    // freeze the time and check the accuracy once more,
+    // in case the test ended within the grace keys
+    pub fn stop(&mut self) {
+        self.end = self.elapsed();
+        self.check_accuracy();
+    }
+
+    // speed of the correct keys in the last window of time, in words of five keys per minute
+    pub fn rolling_wpm(&self, window: Duration) -> f64 {
+        let Some(elapsed) = self.elapsed() else {
+            return 0.;
+        };
+        let window = window.min(elapsed).max(Duration::from_secs(1));
+        let correct_keys = self
+            .keystrokes
+            .iter()
+            .rev()
+            .take_while(|keystroke| keystroke.time + window >= elapsed)
+            .filter(|keystroke| matches!(keystroke.key, Key::Char(_)) && keystroke.correct)
+            .count();
+        correct_keys as f64 / 5. * (60. / window.as_secs_f64())
+    }
+
+    fn check_accuracy(&mut self) {
+        if let Some(min_accuracy) = self.rules.min_accuracy {
+            if self.failure.is_none() && self.accuracy() < min_accuracy {
+                self.failure = Some(Failure::Accuracy(self.accuracy()));
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        // the space after the last word in word input
+        if self.separators.len() == self.words.len() {
+            return true;
+        }
+
+        let target = self.words.last().unwrap();
+        let typed = self.typed.last().unwrap();
+        self.typed.len() == self.words.len()
+            && match (self.rules.input, self.rules.stop_on_error) {
+                (Input::Chars, StopOnError::Off | StopOnError::Letter) => {
+                    typed.len() == target.len()
+                }
+                _ => typed == target,
+            }
+    }
+
+    // keys typed that are still in the text, spaces included
+    pub fn chars_typed(&self) -> usize {
+        self.typed.iter().map(Vec::len).sum::<usize>() + self.separators.len()
+    }
+
+    pub fn words_typed(&self) -> usize {
+        (self.separators.len() + !self.typed.last().unwrap().is_empty() as usize)
+            .min(self.words.len())
+    }
+
+    // words that have been left or completed, with whether they were typed correctly
+    pub fn finished_words(&self) -> impl Iterator<Item = (usize, &[char], bool)> {
+        let finished_words = if self.is_finished() {
+            self.words.len()
+        } else {
+            self.separators.len()
+        };
+        self.words[..finished_words]
+            .iter()
+            .zip(&self.typed)
+            .enumerate()
+            .map(|(index, (target, typed))| (index, target.as_slice(), typed == target))
+    }
+
+    // what became of every char of the text, extra and missed only happen in word input
+    pub fn char_stats(&self) -> CharStats {
+        let mut stats = CharStats::default();
+        for (word_index, typed) in self.typed.iter().enumerate() {
+            let target = &self.words[word_index];
+            for (char_index, key) in typed.iter().enumerate() {
+                match target.get(char_index) {
+                    Some(expected) if expected == key => stats.correct += 1,
+                    Some(_) => stats.incorrect += 1,
+                    None => stats.extra += 1,
+                }
+            }
+            if word_index < self.separators.len() {
+                stats.missed += target.len().saturating_sub(typed.len());
+            }
+        }
+        stats
+    }
+
+    // chars of the text up to the end of the last finished word
+    pub fn completed_chars(&self) -> usize {
+        let finished_words = if self.is_finished() {
+            self.words.len()
+        } else {
+            self.separators.len()
+        };
+        self.words[..finished_words]
+            .iter()
+            .map(|word| word.len() + 1)
+            .sum::<usize>()
+            .min(self.words.iter().map(|word| word.len() + 1).sum::<usize>() - 1)
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        let keys = self.correct_keys + self.incorrect_keys;
+        if keys == 0 {
+            return 100.;
+        }
+        self.correct_keys as f64 / keys as f64 * 100.
+    }
+
+    // redraw the whole text centered on the screen and put the cursor where the next key goes,
+    // with a background color on the other carets
+    pub fn draw(
+        &self,
+        out: &mut impl Write,
+        width: u16,
+        height: u16,
+        carets: &[((usize, usize), Color)],
+    ) -> io::Result<()> {
+        let lines = self.layout(width as usize / 2);
+        let start_height = height.saturating_sub(lines.len() as u16) / 2;
+        let mut cursor_position = (0, 0);
+
+        // the first two and the last row belong to the timer and messages
+        for row in 2..height.saturating_sub(1) {
+            queue!(
+                out,
+                cursor::MoveTo(0, row),
+                terminal::Clear(terminal::ClearType::CurrentLine)
+            )?;
+        }
+
+        for (line_index, line) in lines.iter().enumerate() {
+            let line_width: usize = line.iter().map(|&index| self.word_width(index)).sum();
+            let row = start_height + line_index as u16;
+            let mut column = width.saturating_sub(line_width.saturating_sub(1) as u16) / 2;
+            queue!(out, cursor::MoveTo(column, row))?;
+
+            for &word_index in line {
+                for (char_index, styled) in self.styled_word(word_index).into_iter().enumerate() {
+                    if word_index == self.typed.len() - 1
+                        && char_index == self.typed[word_index].len()
+                    {
+                        cursor_position = (column, row);
+                    }
+                    let styled = match carets
+                        .iter()
+                        .find(|(position, _)| *position == (word_index, char_index))
+                    {
+                        Some(&(_, color)) => styled.on(color),
+                        None => styled,
+                    };
+                    queue!(out, PrintStyledContent(styled))?;
+                    column += 1;
+                }
+            }
+        }
+
+        queue!(out, cursor::MoveTo(cursor_position.0, cursor_position.1))
+    }
+
+    // columns a word takes up, including extra chars and the space after it
+    fn word_width(&self, word_index: usize) -> usize {
+        let typed = self.typed.get(word_index).map_or(0, Vec::len);
+        self.words[word_index].len().max(typed) + 1
+    }
+
+    // word indices of each line, wrapped so no line is longer than length_of_line
+    fn layout(&self, length_of_line: usize) -> Vec<Vec<usize>> {
+        let mut lines: Vec<Vec<usize>> = vec![Vec::new()];
+        let mut line_width = 0;
+
+        for word_index in 0..self.words.len() {
+            let word_width = self.word_width(word_index);
+            if line_width + word_width > length_of_line + 1 && line_width > 0 {
+                lines.push(Vec::new());
+                line_width = 0;
+            }
+            lines.last_mut().unwrap().push(word_index);
+            line_width += word_width;
+        }
+
+        lines
+    }
+
+    // the chars of a word and the space after it, colored by what was typed
+    fn styled_word(&self, word_index: usize) -> Vec<StyledContent<char>> {
+        let target = &self.words[word_index];
+        let typed = self.typed.get(word_index);
+        let finished = word_index < self.separators.len();
+        let mut styled = Vec::new();
+
+        for (char_index, &expected) in target.iter().enumerate() {
+            styled.push(match typed.and_then(|typed| typed.get(char_index)) {
+                Some(&key) if key == expected => expected.green(),
+                Some(' ') => '█'.red(),
+                Some(&key) => key.red(),
+                // skipped over with space in word input
+                None if finished => expected.dark_red().underlined(),
+                None => expected.blue(),
+            });
+        }
+        if let Some(extra) = typed.and_then(|typed| typed.get(target.len()..)) {
+            styled.extend(extra.iter().map(|&key| key.red()));
+        }
+
+        styled.push(match self.separators.get(word_index) {
+            Some(' ') => ' '.green(),
+            Some(&key) => key.red(),
+            None => ' '.blue(),
+        });
+
+        styled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(input: Input, stop_on_error: StopOnError, backspace: Backspace) -> Rules {
+        Rules {
+            input,
+            stop_on_error,
+            backspace,
+            ..Rules::default()
+        }
+    }
+
+    fn typed(text: &str, rules: Rules, keys: &str) -> TypingTest {
+        let mut test = TypingTest::new(text, rules);
+        for key in keys.chars() {
+            test.type_key(key);
+        }
+        test
+    }
+
+    #[test]
+    fn completed_chars() {
+        assert_eq!(typed("ab cd", Rules::default(), "a").completed_chars(), 0);
+        assert_eq!(
+            typed("ab cd", Rules::default(), "ab c").completed_chars(),
+            3
+        );
+        // no space after the last word
+        assert_eq!(
+            typed("ab cd", Rules::default(), "ab cd").completed_chars(),
+            5
+        );
+        // whitespace around the text isn't part of it
+        assert_eq!(
+            typed(" ab  cd ", Rules::default(), "ab cd").completed_chars(),
+            5
+        );
+    }
+
+    #[test]
+    fn char_input() {
+        let test = typed("ab cd", Rules::default(), "ab cd");
+        assert!(test.is_finished());
+        assert_eq!(test.chars_typed(), 5);
+        assert_eq!(test.words_typed(), 2);
+        assert_eq!(test.accuracy(), 100.);
+
+        // a wrong char takes its place, a wrong space still moves on to the next word
+        let test = typed("ab cd", Rules::default(), "axc");
+        assert_eq!(test.position(), (1, 0));
+        assert_eq!(test.incorrect_keys, 2);
+        let stats = test.char_stats();
+        assert_eq!((stats.correct, stats.incorrect), (1, 1));
+        assert_eq!(
+            test.finished_words().collect::<Vec<_>>(),
+            vec![(0, &['a', 'b'][..], false)]
+        );
+
+        // the last word ends the test at its length, right or wrong
+        let test = typed("ab cd", Rules::default(), "ab cx");
+        assert!(test.is_finished());
+        assert_eq!(test.char_stats().incorrect, 1);
+    }
+
+    #[test]
+    fn strict_keeps_the_cursor_on_errors() {
+        let strict = rules(Input::Chars, StopOnError::Letter, Backspace::Allowed);
+        let mut test = typed("ab cd", strict, "ax");
+        assert_eq!(test.position(), (0, 1));
+        assert_eq!(test.incorrect_keys, 1);
+        test.type_key('b');
+        test.type_key('x');
+        assert_eq!(test.position(), (0, 2));
+        test.type_key(' ');
+        assert_eq!(test.position(), (1, 0));
+    }
+
+    #[test]
+    fn stop_on_word_needs_the_word_right() {
+        let stop_on_word = rules(Input::Chars, StopOnError::Word, Backspace::Allowed);
+        let mut test = typed("ab cd", stop_on_word, "ax ");
+        assert_eq!(test.position(), (0, 2));
+        test.backspace();
+        test.type_key('b');
+        test.type_key(' ');
+        assert_eq!(test.position(), (1, 0));
+
+        // the same keys finish the text under other rules
+        let keys = "ab xab cd";
+        assert!(!typed("ab cd", stop_on_word, keys).is_finished());
+        assert!(typed("ab cd", Rules::default(), keys).is_finished());
+    }
+
+    #[test]
+    fn failing() {
+        let sudden_death = Rules {
+            sudden_death: true,
+            ..Rules::default()
+        };
+        assert!(typed("ab cd", sudden_death, "ab").failure.is_none());
+        assert!(matches!(
+            typed("ab cd", sudden_death, "ax").failure,
+            Some(Failure::FirstError)
+        ));
+
+        let min_accuracy = Rules {
+            min_accuracy: Some(90.),
+            ..Rules::default()
+        };
+        // not within the grace keys, unless the test stops there
+        let mut test = typed("aaaaaaaaaaaa", min_accuracy, "b");
+        assert!(test.failure.is_none());
+        test.stop();
+        assert!(matches!(test.failure, Some(Failure::Accuracy(_))));
+        let test = typed("aaaaaaaaaaaa", min_accuracy, "aaaaaaaabb");
+        assert!(matches!(test.failure, Some(Failure::Accuracy(_))));
+        let test = typed("aaaaaaaaaaaa", min_accuracy, "aaaaaaaaab");
+        assert!(test.failure.is_none());
+    }
+
+    #[test]
+    fn backspace() {
+        let mut test = typed("ab cd", Rules::default(), "ab ");
+        test.backspace();
+        assert_eq!(test.position(), (0, 2));
+
+        let confidence = rules(Input::Chars, StopOnError::Off, Backspace::Confidence);
+        let mut test = typed("ab cd", confidence, "ab c");
+        test.backspace();
+        test.backspace();
+        assert_eq!(test.position(), (1, 0));
+
+        let disabled = rules(Input::Chars, StopOnError::Off, Backspace::Disabled);
+        let mut test = typed("ab cd", disabled, "ax");
+        let keys = test.keystrokes.len();
+        test.backspace();
+        assert_eq!(test.position(), (0, 2));
+        assert_eq!(test.keystrokes.len(), keys);
+    }
+
+    #[test]
+    fn word_input() {
+        let words = rules(Input::Words, StopOnError::Off, Backspace::Allowed);
+        // space skips the rest of a word, but not a word not started yet
+        let test = typed("ab cd ef", words, " a cdx ");
+        assert_eq!(test.position(), (2, 0));
+        let stats = test.char_stats();
+        assert_eq!(
+            (stats.correct, stats.incorrect, stats.extra, stats.missed),
+            (3, 0, 1, 1)
+        );
+
+        // a word only holds so many extra chars
+        let test = typed("ab", words, &"a".repeat(20));
+        assert_eq!(test.chars_typed(), 2 + MAX_EXTRA_CHARS);
+
+        // the last word is done when it is right, or with the space after it
+        assert!(typed("ab cd", words, "ab cd").is_finished());
+        let mut test = typed("ab cd", words, "ab cx");
+        assert!(!test.is_finished());
+        test.type_key(' ');
+        assert!(test.is_finished());
+        assert_eq!(test.completed_chars(), 5);
+
+        let stop_on_word = rules(Input::Words, StopOnError::Word, Backspace::Allowed);
+        let test = typed("ab cd", stop_on_word, "ax ");
+        assert_eq!(test.position(), (0, 2));
+    }
+
+    #[test]
+    fn delete_word() {
+        let mut test = typed("ab cd", Rules::default(), "ab c");
+        test.delete_word();
+        assert_eq!(test.position(), (1, 0));
+        test.delete_word();
+        assert_eq!(test.position(), (0, 0));
+        // errors stay counted
+        let mut test = typed("ab cd", Rules::default(), "ax");
+        test.delete_word();
+        assert_eq!(test.incorrect_keys, 1);
+
+        let confidence = rules(Input::Chars, StopOnError::Off, Backspace::Confidence);
+        let mut test = typed("ab cd", confidence, "ab c");
+        test.delete_word();
+        test.delete_word();
+        assert_eq!(test.position(), (1, 0));
+
+        let disabled = rules(Input::Chars, StopOnError::Off, Backspace::Disabled);
+        let mut test = typed("ab cd", disabled, "ab c");
+        test.delete_word();
+        assert_eq!(test.position(), (1, 1));
+    }
+
+    #[test]
+    fn offsets() {
+        let test = TypingTest::new(" ab  cd ", Rules::default());
+        assert_eq!(test.text_len(), 5);
+        assert_eq!(test.position_of(0), (0, 0));
+        assert_eq!(test.position_of(2), (0, 2));
+        assert_eq!(test.position_of(3), (1, 0));
+        assert_eq!(test.position_of(5), (1, 2));
+        // the pace can't go past the end
+        assert_eq!(test.position_of(9), (1, 2));
+    }
+
+    #[test]
+    fn categories_round_trip() {
+        for input in [Input::Chars, Input::Words] {
+            for stop_on_error in [StopOnError::Off, StopOnError::Letter, StopOnError::Word] {
+                for backspace in [
+                    Backspace::Allowed,
+                    Backspace::Confidence,
+                    Backspace::Disabled,
+                ] {
+                    for sudden_death in [false, true] {
+                        for min_accuracy in [None, Some(90.), Some(97.5)] {
+                            let rules = Rules {
+                                input,
+                                stop_on_error,
+                                backspace,
+                                sudden_death,
+                                min_accuracy,
+                            };
+                            let category = rules.category();
+                            let parsed = Rules::from_category(&category).unwrap();
+                            assert_eq!(parsed.category(), category);
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(Rules::default().category(), "normal");
+        assert!(Rules::from_category("bogus").is_none());
+    }
+}
@@ -1,4 +1,4 @@
-pub const DEFAULT_ENGLISH: [&str; 10000] = [
+pub static DEFAULT_ENGLISH: [&str; 10000] = [
     "the",
     "of",
     "and",